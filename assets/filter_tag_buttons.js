@@ -1,55 +1,155 @@
+// The list the filter chips refresh, its `data-list-url` is where the refreshed list comes from,
+// like `/blog_post_list` or `/project_list`.
+const post_container = document.querySelector("[data-list-url]");
+// Only on `/blog`, the home page shows the list without it.
+const search_form = document.querySelector("#post_search_form");
+const search_input = document.querySelector("#post_search");
 
-const post_container = document.querySelector("#blog_posts");
-
-// Takes an array of filters and manually creates a query string from it.
+// Takes an array of tags and manually creates a query string from it.
 // Qs.stringify uses an array notation that's incompatible with serde_qs,
 // on the Rust-server side.
-function filters_to_string(filters) {
+function tags_to_string(name, tags) {
 	var result = "";
-	for (var i = 0; i < filters.length; i++) {
-		result += `filters[${i}]=${filters[i]}&`;
+	for (var i = 0; i < tags.length; i++) {
+		result += `${name}[${i}]=${tags[i]}&`;
+	}
+	return result;
+}
+
+// The included and excluded tags, and how the included ones combine.
+function filters_to_string(query) {
+	var result = tags_to_string("filters", query.filters ?? []);
+	result += tags_to_string("exclude", query.exclude ?? []);
+	if (query.mode != null) {
+		result += `mode=${query.mode}&`;
+	}
+	return result;
+}
+
+// Builds the full query string for the post list from the parsed query.
+function query_to_string(query) {
+	var result = filters_to_string(query);
+	if (query.q != null && query.q.trim() != "") {
+		result += `q=${encodeURIComponent(query.q.trim())}&`;
+	}
+	if (query.sort != null) {
+		result += `sort=${query.sort}&`;
+	}
+	// Keep preview mode (drafts and scheduled posts) when refreshing the list.
+	if (query.preview != null) {
+		result += `preview=${encodeURIComponent(query.preview)}&`;
 	}
 	return result;
 }
 
+function current_query() {
+	return Qs.parse(location.search, { ignoreQueryPrefix: true });
+}
+
 const LOADER_HTML = "<progress class=\"circle large\"/>";
 const MSECS_BEFORE_LOADER = 200;
 function show_loader() {
 	post_container.innerHTML = LOADER_HTML;
 }
 
-async function toggle_tag_filter(tag, _) {
-	var query = Qs.parse(location.search, { ignoreQueryPrefix: true });
-	if (query.filters == null) {
-		query.filters = [tag];
-	} else {
-		var tag_index = query.filters.indexOf(tag);
-		if (tag_index == -1) {
-			query.filters.push(tag);
-		} else {
-			query.filters.splice(tag_index, 1);
-		}
-	}
-	const filter_string = filters_to_string(query.filters);
+// Incremented on every refresh, so a slow response can't overwrite a newer one while typing.
+var latest_refresh = 0;
+async function refresh_post_list(query) {
+	const refresh = ++latest_refresh;
+	const query_string = query_to_string(query);
 	const timeout_before_loader = window.setTimeout(show_loader, MSECS_BEFORE_LOADER);
-	const blog_posts = await fetch("/blog_post_list?" + filter_string);
+	const blog_posts = await fetch(post_container.dataset.listUrl + "?" + query_string);
+	const blog_posts_html = await blog_posts.text();
+	if (refresh != latest_refresh) {
+		window.clearTimeout(timeout_before_loader);
+		return;
+	}
+	window.clearTimeout(timeout_before_loader);
 	if (!blog_posts.ok) {
 		post_container.innerHTML = "Error: " + blog_posts.statusText;
 		return;
 	}
-	post_container.innerHTML = await blog_posts.text();
-	window.clearTimeout(timeout_before_loader);
+	post_container.innerHTML = blog_posts_html;
 	bind_tag_buttons_on_click();
 	var current_url = new URL(location);
-	current_url.search = filter_string;
+	current_url.search = query_string;
 	history.replaceState(null, "", current_url.toString());
 }
 
+// Cycles a tag through off, included and excluded.
+async function toggle_tag_filter(tag, _) {
+	var query = current_query();
+	query.filters = query.filters ?? [];
+	query.exclude = query.exclude ?? [];
+	var filter_index = query.filters.indexOf(tag);
+	var exclude_index = query.exclude.indexOf(tag);
+	if (filter_index != -1) {
+		query.filters.splice(filter_index, 1);
+		query.exclude.push(tag);
+	} else if (exclude_index != -1) {
+		query.exclude.splice(exclude_index, 1);
+	} else {
+		query.filters.push(tag);
+	}
+	await refresh_post_list(query);
+}
+
+async function set_filter_mode(mode, _) {
+	var query = current_query();
+	query.mode = mode;
+	await refresh_post_list(query);
+}
+
+async function set_post_sort(sort, _) {
+	var query = current_query();
+	query.sort = sort;
+	await refresh_post_list(query);
+}
+
+const MSECS_BEFORE_SEARCH = 250;
+var search_timeout = null;
+// Waits until typing pauses before searching.
+function search_posts() {
+	window.clearTimeout(search_timeout);
+	search_timeout = window.setTimeout(async () => {
+		var query = current_query();
+		query.q = search_input.value;
+		await refresh_post_list(query);
+	}, MSECS_BEFORE_SEARCH);
+}
+
+function bind_search_input() {
+	if (search_form == null) {
+		return;
+	}
+	search_input.oninput = search_posts;
+	search_form.onsubmit = (event) => {
+		event.preventDefault();
+		search_posts();
+	};
+}
+
 function bind_tag_buttons_on_click() {
 	var tag_btns = document.querySelectorAll("#filter_tag");
 	tag_btns.forEach((btn) => {
-		btn.onclick = toggle_tag_filter.bind(null, btn.dataset.tag);
+		if (post_container == null) {
+			// Lists that can't be filtered, like on `/tags/{tag}`, link to the tag's page instead.
+			btn.onclick = () => location.assign(`/tags/${btn.dataset.tag.toLowerCase()}`);
+		} else {
+			btn.onclick = toggle_tag_filter.bind(null, btn.dataset.tag);
+		}
+	});
+	var mode_btns = document.querySelectorAll("#filter_mode");
+	mode_btns.forEach((btn) => {
+		btn.onclick = set_filter_mode.bind(null, btn.dataset.mode);
+	});
+	var sort_btns = document.querySelectorAll("#sort_posts");
+	sort_btns.forEach((btn) => {
+		btn.onclick = set_post_sort.bind(null, btn.dataset.sort);
 	});
 }
 
-window.onload = bind_tag_buttons_on_click;
+window.onload = () => {
+	bind_tag_buttons_on_click();
+	bind_search_input();
+};
@@ -0,0 +1,15 @@
+import katex from "https://cdn.jsdelivr.net/npm/katex@0.16/dist/katex.mjs";
+
+// The server renders math as `code.language-math` elements holding the TeX source.
+// `math-display` blocks are wrapped in a <pre>, which gets replaced by the rendered block.
+function render_math() {
+	document.querySelectorAll("code.language-math").forEach((el) => {
+		const display_mode = el.classList.contains("math-display");
+		const target = display_mode ? el.parentElement : el;
+		const rendered = document.createElement(display_mode ? "div" : "span");
+		katex.render(el.textContent, rendered, { displayMode: display_mode, throwOnError: false });
+		target.replaceWith(rendered);
+	});
+}
+
+render_math();
@@ -0,0 +1,67 @@
+// Status page for the site owner, at `/admin/status?preview=<secret>`.
+// Only reachable when a preview secret is configured, see `config::SiteConfig`.
+
+use std::sync::{Arc, Mutex};
+
+use axum::{extract::State, http::StatusCode};
+use maud::html;
+
+use crate::{
+    blog::PreviewMode,
+    helpers::{PageMeta, PageType, WebPage},
+    BlogState,
+};
+
+pub async fn status_page(
+    preview: PreviewMode,
+    State(state_mutex): State<Arc<Mutex<BlogState>>>,
+) -> WebPage {
+    if !preview.0 {
+        return WebPage::not_found("Page not found.");
+    }
+    let mut state = state_mutex.lock().unwrap();
+    state.refresh();
+    WebPage {
+        title: "Site Status".into(),
+        content: html! {
+            h4 .large { "Site Status" }
+            article .border {
+                h6 { "Blog Index" }
+                p { (state.index.posts.len()) " posts indexed, " (state.diagnostics.len()) " problems found." }
+            }
+            @let cache_stats = state.render_cache.stats();
+            article .border {
+                h6 { "Render Cache" }
+                p {
+                    (cache_stats.entries) " posts cached, "
+                    (cache_stats.hits) " hits, " (cache_stats.misses) " misses."
+                }
+            }
+            @if !state.diagnostics.is_empty() {
+                table .stripes {
+                    thead {
+                        tr { th { "Severity" } th { "File" } th { "Problem" } }
+                    }
+                    tbody {
+                        @for diagnostic in &state.diagnostics {
+                            tr {
+                                td {
+                                    @if diagnostic.is_fatal() {
+                                        span .chip .error-container { "Skipped" }
+                                    } @else {
+                                        span .chip .tertiary-container { "Warning" }
+                                    }
+                                }
+                                td { code { (diagnostic.location()) } }
+                                td { (diagnostic.kind) }
+                            }
+                        }
+                    }
+                }
+            }
+        },
+        page_type: PageType::Error,
+        status: StatusCode::OK,
+        meta: PageMeta::default(),
+    }
+}
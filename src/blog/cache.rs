@@ -0,0 +1,88 @@
+// Rendered HTML of each post, kept in `BlogState` between requests.
+// Entries are checked against a hash of the post's file, so editing a post renders only that post
+// again. The file is still read on every request, but reading is cheap next to rendering.
+
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    io,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use crate::blog::{
+    render::{self, RenderedMarkdown},
+    BlogPostIndex, BlogPostInfo,
+};
+
+struct CachedRender {
+    content_hash: u64,
+    rendered: Arc<RenderedMarkdown>,
+}
+
+// Locks of its own so pages can render through a shared `&BlogState`.
+#[derive(Default)]
+pub struct RenderCache {
+    // By `BlogPostInfo::path`.
+    entries: Mutex<HashMap<PathBuf, CachedRender>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+pub struct RenderCacheStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+fn content_hash(md: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    md.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl RenderCache {
+    // The post's HTML, rendered again only when its file changed since it was last rendered.
+    pub fn render(&self, post_info: &BlogPostInfo) -> Result<Arc<RenderedMarkdown>, io::Error> {
+        let md = post_info.load_markdown()?;
+        let content_hash = content_hash(&md);
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(cached) = entries.get(&post_info.path) {
+            if cached.content_hash == content_hash {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(cached.rendered.clone());
+            }
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let rendered = Arc::new(render::render_markdown(&md, post_info.markdown));
+        entries.insert(
+            post_info.path.clone(),
+            CachedRender {
+                content_hash,
+                rendered: rendered.clone(),
+            },
+        );
+        Ok(rendered)
+    }
+
+    // Drops posts that are no longer in `index`, after it's reloaded.
+    pub fn retain_indexed(&self, index: &BlogPostIndex) {
+        self.entries.lock().unwrap().retain(|path, _| {
+            index
+                .posts
+                .values()
+                .any(|post_info| post_info.path == *path)
+        });
+    }
+
+    pub fn stats(&self) -> RenderCacheStats {
+        RenderCacheStats {
+            entries: self.entries.lock().unwrap().len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+// Problems found while indexing `./posts/` and loading `tags.yml`.
+// A broken post is skipped (or loses the broken field) instead of taking down the whole blog.
+
+use std::fmt::Display;
+
+use crate::blog::InvalidBlogID;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum IndexDiagnosticKind {
+    // The file could not be read. The post is skipped.
+    Io(String),
+    // No `---` delimited frontmatter block. The post is skipped.
+    MissingFrontmatter,
+    // Frontmatter (or `tags.yml`) is not valid YAML or is missing required fields.
+    // The post is skipped, or every tag for `tags.yml`.
+    Yaml(String),
+    // No `date:` in the frontmatter. The post is dated by the file's modified time, which changes
+    // on every checkout.
+    MissingDate,
+    // A tag in `tags:` is not in `tags.yml`. The post is indexed without it.
+    UnknownTag(String),
+    // A slug in a post's `projects:` has no file in `./projects/`. The post is indexed without it.
+    UnknownProject(String),
+    // A tag id or alias in `tags.yml` is already used by an earlier tag. The later one is ignored.
+    DuplicateTag(String),
+    // Another post, named second, already has this slug. The post is skipped.
+    DuplicateSlug(String, String),
+    // An alias is another post's slug or alias, named second. The alias is ignored.
+    DuplicateAlias(String, String),
+    // The slug, or the file name when there's no slug, can't be used in a URL. The post is
+    // skipped.
+    InvalidSlug(String, InvalidBlogID),
+    // An alias can't be used in a URL. The alias is ignored.
+    InvalidAlias(String, InvalidBlogID),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct IndexDiagnostic {
+    pub file_name: String,
+    // 1-based line in the markdown file, when known.
+    pub line: Option<usize>,
+    pub kind: IndexDiagnosticKind,
+}
+
+impl IndexDiagnostic {
+    pub fn new(
+        file_name: impl Into<String>,
+        line: Option<usize>,
+        kind: IndexDiagnosticKind,
+    ) -> Self {
+        Self {
+            file_name: file_name.into(),
+            line,
+            kind,
+        }
+    }
+
+    // Whether the post was left out of the index because of this problem.
+    pub fn is_fatal(&self) -> bool {
+        !matches!(
+            self.kind,
+            IndexDiagnosticKind::MissingDate
+                | IndexDiagnosticKind::UnknownTag(_)
+                | IndexDiagnosticKind::UnknownProject(_)
+                | IndexDiagnosticKind::DuplicateTag(_)
+                | IndexDiagnosticKind::DuplicateAlias(_, _)
+                | IndexDiagnosticKind::InvalidAlias(_, _)
+        )
+    }
+
+    pub fn location(&self) -> String {
+        match self.line {
+            Some(line) => format!("{}:{}", self.file_name, line),
+            None => self.file_name.clone(),
+        }
+    }
+}
+
+// serde_yml's message without its " at line X column Y" suffix, for when the line is reported
+// separately.
+pub fn yaml_error_message(err: &serde_yml::Error) -> String {
+    let message = err.to_string();
+    match (err.location(), message.rsplit_once(" at line ")) {
+        (Some(_), Some((message, _))) => message.to_string(),
+        _ => message,
+    }
+}
+
+impl Display for IndexDiagnosticKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IndexDiagnosticKind::Io(err) => write!(f, "could not read file: {}", err),
+            IndexDiagnosticKind::MissingFrontmatter => {
+                f.write_str("missing `---` frontmatter block")
+            }
+            IndexDiagnosticKind::Yaml(err) => write!(f, "invalid YAML: {}", err),
+            IndexDiagnosticKind::MissingDate => {
+                f.write_str("no `date`, falling back to the file's modified time")
+            }
+            IndexDiagnosticKind::UnknownTag(tag) => {
+                write!(f, "unknown tag `{}`, add it to tags.yml", tag)
+            }
+            IndexDiagnosticKind::UnknownProject(slug) => {
+                write!(f, "unknown project `{}`, no projects/{}.md", slug, slug)
+            }
+            IndexDiagnosticKind::DuplicateTag(tag) => {
+                write!(f, "tag id or alias `{}` is already used", tag)
+            }
+            IndexDiagnosticKind::DuplicateSlug(slug, other_file) => {
+                write!(f, "slug `{}` is already used by {}", slug, other_file)
+            }
+            IndexDiagnosticKind::DuplicateAlias(alias, other_file) => {
+                write!(f, "alias `{}` is already used by {}", alias, other_file)
+            }
+            IndexDiagnosticKind::InvalidSlug(slug, err) => {
+                write!(f, "slug {:?} {}", slug, err)
+            }
+            IndexDiagnosticKind::InvalidAlias(alias, err) => {
+                write!(f, "alias {:?} {}", alias, err)
+            }
+        }
+    }
+}
+
+impl Display for IndexDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = if self.is_fatal() { "error" } else { "warning" };
+        write!(f, "{}: {}: {}", severity, self.location(), self.kind)
+    }
+}
@@ -0,0 +1,232 @@
+// RSS 2.0, Atom and JSON Feed versions of the blog.
+// Every feed accepts the same `filters[n]=Tag` query as `/blog` for per-tag feeds.
+// Drafts and scheduled posts are never included.
+
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use maud::{html, Markup, PreEscaped};
+use serde::Serialize;
+use serde_qs::axum::OptionalQsQuery;
+
+use crate::{
+    blog::{
+        cache::RenderCache,
+        page::{TagFilterSet, BLOG_DESCRIPTION},
+        render::{inline_plain_text, render_inline_markdown},
+        BlogID, BlogPostInfo, PreviewMode,
+    },
+    config::site_config,
+    BlogState,
+};
+
+const XML_DECLARATION: PreEscaped<&str> = PreEscaped(r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+
+// A post with everything a feed entry needs.
+struct FeedEntry {
+    url: String,
+    title: String,
+    // Plain text, for the RSS description and Atom and JSON summaries.
+    tagline: String,
+    categories: Vec<String>,
+    published_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+    content_html: String,
+}
+
+struct Feed {
+    title: String,
+    // Path of the feed itself, including the filter query.
+    self_path: String,
+    entries: Vec<FeedEntry>,
+}
+
+impl Feed {
+    fn updated_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.entries
+            .iter()
+            .map(|entry| entry.updated_at)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+fn feed_entry(blog_id: &BlogID, post_info: &BlogPostInfo, render_cache: &RenderCache) -> FeedEntry {
+    let content_html = match render_cache.render(post_info) {
+        Ok(rendered) => rendered.html.clone().into_string(),
+        Err(err) => {
+            eprintln!("Error rendering {} for feed: {}", blog_id, err);
+            html! { p { (render_inline_markdown(&post_info.tagline)) } }.into_string()
+        }
+    };
+    FeedEntry {
+        url: site_config().url(&blog_id.url_path()),
+        title: post_info.title.clone(),
+        tagline: inline_plain_text(&post_info.tagline),
+        categories: post_info.tags.iter().map(|tag| tag.to_string()).collect(),
+        published_at: post_info.published_at,
+        updated_at: post_info.updated.unwrap_or(post_info.published_at),
+        content_html,
+    }
+}
+
+fn build_feed(
+    state_mutex: &Mutex<BlogState>,
+    filter_set: Option<TagFilterSet>,
+    path: &str,
+) -> Feed {
+    let mut state = state_mutex.lock().unwrap();
+    state.refresh();
+    let filter_set = filter_set.map(|filter_set| filter_set.resolved(&state.tags));
+    let mut title = site_config().site_title.clone();
+    let mut self_path = path.to_string();
+    if let Some(filter_set) = &filter_set {
+        if filter_set.has_tag_filters() {
+            title = format!("{} ({})", title, filter_set.describe(&state.tags));
+            self_path = format!("{}?{}", path, filter_set.to_query_string());
+        }
+    }
+    let entries = state
+        .index
+        .as_sorted_vec(PreviewMode(false))
+        .into_iter()
+        .filter(|(_, post_info)| {
+            filter_set
+                .as_ref()
+                .is_none_or(|filter_set| filter_set.matches(post_info))
+        })
+        .map(|(blog_id, post_info)| feed_entry(blog_id, post_info, &state.render_cache))
+        .collect();
+    Feed {
+        title,
+        self_path,
+        entries,
+    }
+}
+
+fn feed_response(content_type: &'static str, body: String) -> Response {
+    ([(header::CONTENT_TYPE, content_type)], body).into_response()
+}
+
+pub async fn rss_feed(
+    OptionalQsQuery(filter_set): OptionalQsQuery<TagFilterSet>,
+    State(state_mutex): State<Arc<Mutex<BlogState>>>,
+) -> Response {
+    let feed = build_feed(&state_mutex, filter_set, "/feed.xml");
+    let markup: Markup = html! {
+        (XML_DECLARATION)
+        rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/" {
+            channel {
+                title { (feed.title) }
+                link { (site_config().url("/blog")) }
+                description { (BLOG_DESCRIPTION) }
+                language { "en" }
+                lastBuildDate { (feed.updated_at().to_rfc2822()) }
+                @for entry in &feed.entries {
+                    item {
+                        title { (entry.title) }
+                        link { (entry.url) }
+                        guid isPermaLink="true" { (entry.url) }
+                        pubDate { (entry.published_at.to_rfc2822()) }
+                        @for category in &entry.categories {
+                            category { (category) }
+                        }
+                        description { (entry.tagline) }
+                        content:encoded { (entry.content_html) }
+                    }
+                }
+            }
+        }
+    };
+    feed_response("application/rss+xml; charset=utf-8", markup.into_string())
+}
+
+pub async fn atom_feed(
+    OptionalQsQuery(filter_set): OptionalQsQuery<TagFilterSet>,
+    State(state_mutex): State<Arc<Mutex<BlogState>>>,
+) -> Response {
+    let feed = build_feed(&state_mutex, filter_set, "/atom.xml");
+    let markup: Markup = html! {
+        (XML_DECLARATION)
+        feed xmlns="http://www.w3.org/2005/Atom" {
+            title { (feed.title) }
+            id { (site_config().url(&feed.self_path)) }
+            link rel="self" href=(site_config().url(&feed.self_path)) {}
+            link rel="alternate" type="text/html" href=(site_config().url("/blog")) {}
+            updated { (feed.updated_at().to_rfc3339()) }
+            author { name { "Jordi Gulley" } }
+            @for entry in &feed.entries {
+                entry {
+                    title { (entry.title) }
+                    id { (entry.url) }
+                    link rel="alternate" type="text/html" href=(entry.url) {}
+                    published { (entry.published_at.to_rfc3339()) }
+                    updated { (entry.updated_at.to_rfc3339()) }
+                    summary { (entry.tagline) }
+                    @for category in &entry.categories {
+                        category term=(category) {}
+                    }
+                    content type="html" { (entry.content_html) }
+                }
+            }
+        }
+    };
+    feed_response("application/atom+xml; charset=utf-8", markup.into_string())
+}
+
+// https://www.jsonfeed.org/version/1.1/
+#[derive(Serialize)]
+struct JsonFeed<'a> {
+    version: &'static str,
+    title: &'a str,
+    home_page_url: String,
+    feed_url: String,
+    items: Vec<JsonFeedItem<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonFeedItem<'a> {
+    id: &'a str,
+    url: &'a str,
+    title: &'a str,
+    summary: &'a str,
+    content_html: &'a str,
+    date_published: String,
+    date_modified: String,
+    tags: &'a [String],
+}
+
+pub async fn json_feed(
+    OptionalQsQuery(filter_set): OptionalQsQuery<TagFilterSet>,
+    State(state_mutex): State<Arc<Mutex<BlogState>>>,
+) -> Response {
+    let feed = build_feed(&state_mutex, filter_set, "/feed.json");
+    let json_feed = JsonFeed {
+        version: "https://jsonfeed.org/version/1.1",
+        title: &feed.title,
+        home_page_url: site_config().url("/blog"),
+        feed_url: site_config().url(&feed.self_path),
+        items: feed
+            .entries
+            .iter()
+            .map(|entry| JsonFeedItem {
+                id: &entry.url,
+                url: &entry.url,
+                title: &entry.title,
+                summary: &entry.tagline,
+                content_html: &entry.content_html,
+                date_published: entry.published_at.to_rfc3339(),
+                date_modified: entry.updated_at.to_rfc3339(),
+                tags: &entry.categories,
+            })
+            .collect(),
+    };
+    feed_response(
+        "application/feed+json; charset=utf-8",
+        serde_json::to_string(&json_feed).unwrap(),
+    )
+}
@@ -0,0 +1,134 @@
+// YAML frontmatter shared by posts in `./posts/` and projects in `./projects/`.
+// Problems are reported with line numbers in the markdown file, see `diagnostics`.
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use matter::matter as separate_frontmatter_and_content;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+
+use crate::{
+    blog::diagnostics::{yaml_error_message, IndexDiagnosticKind},
+    tags::{ContentTag, TagRegistry},
+};
+
+// `tags:` is parsed on its own so an unknown tag doesn't reject the whole file.
+#[derive(Deserialize)]
+struct FrontmatterTags {
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+pub struct Frontmatter<T> {
+    pub info: T,
+    // Known tags from `tags:`, without duplicates.
+    pub tags: Vec<ContentTag>,
+    // The markdown after the frontmatter.
+    pub content: String,
+    yaml: String,
+    // Line of the file the frontmatter starts on.
+    first_line: usize,
+}
+
+impl<T> Frontmatter<T> {
+    // Line of the file where `field:` is set, for diagnostics.
+    pub fn field_line(&self, field: &str) -> Option<usize> {
+        self.yaml
+            .lines()
+            .position(|line| {
+                line.trim_start()
+                    .strip_prefix(field)
+                    .is_some_and(|rest| rest.starts_with(':'))
+            })
+            .map(|line| self.first_line + line)
+    }
+
+    // Line of the file where `value` appears in the `field:` list, or the field itself.
+    pub fn list_item_line(&self, field: &str, value: &str) -> Option<usize> {
+        let field_line = self.field_line(field)?;
+        self.yaml
+            .lines()
+            .skip(field_line - self.first_line)
+            .position(|line| line.contains(value))
+            .map(|offset| field_line + offset)
+    }
+}
+
+// Reports missing or invalid frontmatter and unknown tags through `report`, returning `None`
+// when the file can't be used.
+pub fn parse_frontmatter<T: DeserializeOwned>(
+    md: &str,
+    tags: &TagRegistry,
+    mut report: impl FnMut(Option<usize>, IndexDiagnosticKind),
+) -> Option<Frontmatter<T>> {
+    let (yaml, content) = match separate_frontmatter_and_content(md) {
+        Some(frontmatter_and_content) => frontmatter_and_content,
+        None => {
+            report(Some(1), IndexDiagnosticKind::MissingFrontmatter);
+            return None;
+        }
+    };
+    // `matter` trims the frontmatter, so find where it starts to report file line numbers.
+    let first_line = md
+        .find(&yaml)
+        .map_or(2, |offset| md[..offset].lines().count() + 1);
+    let parsed = serde_yml::from_str::<T>(&yaml)
+        .and_then(|info| serde_yml::from_str::<FrontmatterTags>(&yaml).map(|tags| (info, tags)));
+    let (info, FrontmatterTags { tags: tag_names }) = match parsed {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            // serde_yml's own line is relative to the frontmatter.
+            let line = err
+                .location()
+                .map(|location| first_line + location.line() - 1);
+            report(line, IndexDiagnosticKind::Yaml(yaml_error_message(&err)));
+            return None;
+        }
+    };
+    let mut frontmatter = Frontmatter {
+        info,
+        tags: Vec::new(),
+        content,
+        yaml,
+        first_line,
+    };
+    for tag in tag_names {
+        match tags.resolve(&tag) {
+            Some(content_tag) if !frontmatter.tags.contains(content_tag) => {
+                frontmatter.tags.push(content_tag.clone())
+            }
+            Some(_) => {}
+            None => {
+                let line = frontmatter.list_item_line("tags", &tag);
+                report(line, IndexDiagnosticKind::UnknownTag(tag));
+            }
+        }
+    }
+    Some(frontmatter)
+}
+
+// `date` style fields accept `2025-07-14`, `2025-07-14 09:30` or a full RFC 3339 timestamp.
+pub fn deserialize_frontmatter_date<'de, D>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let date_str = match Option::<String>::deserialize(deserializer)? {
+        Some(date_str) => date_str,
+        None => return Ok(None),
+    };
+    parse_frontmatter_date(date_str.trim())
+        .map(Some)
+        .ok_or_else(|| serde::de::Error::custom(format!("invalid date \"{}\"", date_str)))
+}
+
+fn parse_frontmatter_date(date_str: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(date_str) {
+        return Some(date_time.with_timezone(&Utc));
+    }
+    if let Ok(date_time) = NaiveDateTime::parse_from_str(date_str, "%Y-%m-%d %H:%M") {
+        return Some(date_time.and_utc());
+    }
+    NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
+        .ok()
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+}
@@ -0,0 +1,168 @@
+// Server-side syntax highlighting for fenced code blocks.
+// Code is tokenized with syntect and emitted as `hl-` prefixed classes. `/highlight.css` maps them
+// to colours for the site's light and dark schemes, so no client-side JS is needed.
+//
+// The fence info string supports a few extras after the language:
+// ```rust {3-5} linenos
+// `{1,3-5}` marks lines and `linenos` numbers every line.
+
+use std::{collections::HashSet, sync::OnceLock};
+
+use axum::{
+    http::header,
+    response::{IntoResponse, Response},
+};
+use maud::{html, Markup, PreEscaped};
+use syntect::{
+    highlighting::ThemeSet,
+    html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+const LIGHT_THEME: &str = "InspiredGitHub";
+const DARK_THEME: &str = "base16-ocean.dark";
+
+static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+static HIGHLIGHT_CSS: OnceLock<String> = OnceLock::new();
+
+fn syntax_set() -> &'static SyntaxSet {
+    SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+// Options parsed from the fence info string after the language.
+#[derive(Default, Debug)]
+pub struct CodeBlockOptions {
+    pub line_numbers: bool,
+    pub highlighted_lines: HashSet<usize>,
+}
+
+impl CodeBlockOptions {
+    pub fn parse(meta: Option<&str>) -> Self {
+        let mut options = CodeBlockOptions::default();
+        for token in meta.unwrap_or_default().split_whitespace() {
+            match token {
+                "linenos" | "showLineNumbers" => options.line_numbers = true,
+                _ if token.starts_with('{') && token.ends_with('}') => {
+                    options.highlighted_lines = parse_line_ranges(&token[1..token.len() - 1]);
+                }
+                _ => {}
+            }
+        }
+        options
+    }
+}
+
+// "1,3-5" => {1, 3, 4, 5}. Malformed ranges are ignored.
+fn parse_line_ranges(ranges: &str) -> HashSet<usize> {
+    let mut lines = HashSet::new();
+    for range in ranges.split(',') {
+        let range = range.trim();
+        match range.split_once('-') {
+            Some((start, end)) => {
+                if let (Ok(start), Ok(end)) = (start.trim().parse::<usize>(), end.trim().parse()) {
+                    lines.extend(start..=end);
+                }
+            }
+            None => {
+                if let Ok(line) = range.parse() {
+                    lines.insert(line);
+                }
+            }
+        }
+    }
+    lines
+}
+
+// Highlights `code` and wraps every line in a `span.line` so lines can be numbered and marked.
+pub fn highlight_code_block(code: &str, lang: Option<&str>, options: &CodeBlockOptions) -> Markup {
+    let syntax_set = syntax_set();
+    let syntax = lang
+        .and_then(|lang| syntax_set.find_syntax_by_token(lang))
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, syntax_set, CLASS_STYLE);
+    // The `newlines` syntaxes expect every line, including the last, to end with a newline.
+    let code = match code.ends_with('\n') {
+        true => code.to_string(),
+        false => format!("{}\n", code),
+    };
+    let highlighted = LinesWithEndings::from(&code)
+        .try_for_each(|line| generator.parse_html_for_line_which_includes_newline(line))
+        .map(|_| generator.finalize())
+        .unwrap_or_else(|err| {
+            eprintln!("Error highlighting {:?} code block: {}", lang, err);
+            html! { (code) }.into_string()
+        });
+    html! {
+        pre .hl-code .line-numbers[options.line_numbers] data-lang=[lang] {
+            code class=[lang.map(|lang| format!("language-{}", lang))] {
+                @for (i, line) in split_lines(&highlighted).into_iter().enumerate() {
+                    span .line .highlighted[options.highlighted_lines.contains(&(i + 1))] {
+                        (PreEscaped(line))
+                    }
+                    "\n"
+                }
+            }
+        }
+    }
+}
+
+// Splits highlighted HTML into lines, closing the spans still open at the end of each line
+// and reopening them on the next, so every line is balanced HTML on its own.
+fn split_lines(html: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut open_tags: Vec<&str> = Vec::new();
+    let mut line = String::new();
+    let mut line_has_text = false;
+    let mut rest = html;
+    while !rest.is_empty() {
+        if rest.starts_with("</span>") {
+            open_tags.pop();
+            line.push_str("</span>");
+            rest = &rest["</span>".len()..];
+        } else if rest.starts_with("<span") {
+            let tag_end = rest.find('>').map_or(rest.len(), |i| i + 1);
+            open_tags.push(&rest[..tag_end]);
+            line.push_str(&rest[..tag_end]);
+            rest = &rest[tag_end..];
+        } else if rest.starts_with('\n') {
+            line.push_str(&"</span>".repeat(open_tags.len()));
+            lines.push(std::mem::take(&mut line));
+            line.extend(open_tags.iter().copied());
+            line_has_text = false;
+            rest = &rest[1..];
+        } else {
+            let text_end = rest.find(['<', '\n']).unwrap_or(rest.len());
+            line.push_str(&rest[..text_end]);
+            line_has_text = true;
+            rest = &rest[text_end..];
+        }
+    }
+    // Whatever follows the final newline is only the generator closing its spans.
+    if line_has_text {
+        lines.push(line);
+    }
+    lines
+}
+
+fn build_highlight_css() -> String {
+    let theme_set = ThemeSet::load_defaults();
+    let light_css = css_for_theme_with_class_style(&theme_set.themes[LIGHT_THEME], CLASS_STYLE)
+        .unwrap_or_default();
+    let dark_css = css_for_theme_with_class_style(&theme_set.themes[DARK_THEME], CLASS_STYLE)
+        .unwrap_or_default();
+    // The light theme applies by default, the dark theme is nested under beercss' `body.dark`.
+    format!("{}\nbody.dark {{\n{}\n}}\n", light_css, dark_css)
+}
+
+pub async fn highlight_css() -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, "text/css; charset=utf-8"),
+            (header::CACHE_CONTROL, "public, max-age=86400"),
+        ],
+        HIGHLIGHT_CSS.get_or_init(build_highlight_css).clone(),
+    )
+        .into_response()
+}
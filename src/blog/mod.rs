@@ -1,100 +1,550 @@
-use matter::matter as separate_frontmatter_and_content;
-use maud::{html, Markup, PreEscaped};
+use axum::{
+    extract::{FromRequestParts, Query},
+    http::request::Parts,
+};
+use chrono::{DateTime, Utc};
+use maud::{html, Markup};
 use serde::Deserialize;
-use std::{collections::HashMap, io, path::Path, str::FromStr, time::SystemTime};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs::DirEntry,
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
 
-use crate::{helpers::remove_quotes, projects::ContentTag};
+use crate::{
+    blog::{
+        diagnostics::{IndexDiagnostic, IndexDiagnosticKind},
+        frontmatter::{deserialize_frontmatter_date, parse_frontmatter, Frontmatter},
+        render::{slugify, MarkdownExtensions},
+    },
+    config::site_config,
+    projects::{ProjectID, ProjectIndex},
+    tags::{ContentTag, TagRegistry},
+};
 
+pub mod cache;
+pub mod diagnostics;
+pub mod feed;
+pub mod frontmatter;
+pub mod highlight;
 pub mod page;
+pub mod related;
+pub mod render;
+pub mod search;
+pub mod series;
 
-const BLOG_POST_FOLDER_PATH: &'static str = "./posts/";
+pub const BLOG_POST_FOLDER_PATH: &str = "./posts/";
 
-#[derive(Default, Clone, PartialEq, Eq, Hash, Debug, Deserialize)]
-#[serde(transparent)]
+// A post's URL segment, `/blog/{slug}`. Only built through `BlogID::new`, so it never contains
+// path separators, `..`, control characters or anything else that isn't safe in a URL. It's never
+// used as a path either, posts are read from the path the index resolved, see `BlogPostInfo::path`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Deserialize)]
+#[serde(try_from = "String")]
 pub struct BlogID {
-    pub file_name: String,
+    // The post's `slug`, or its file name without `.md` when it has none.
+    slug: String,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum InvalidBlogID {
+    Empty,
+    // `/` or `\`.
+    Separator,
+    // `.` on its own, or `..` anywhere.
+    DotSegment,
+    ControlCharacter,
+    // Anything but letters, digits, `-`, `_`, `.` and `~`, such as `#`, `?`, `%` or a space.
+    UnsafeCharacter,
 }
 
 impl BlogID {
-    pub const fn empty() -> Self {
-        Self {
-            file_name: String::new(),
+    pub fn new(slug: impl Into<String>) -> Result<Self, InvalidBlogID> {
+        let slug = slug.into();
+        if slug.is_empty() {
+            return Err(InvalidBlogID::Empty);
+        }
+        if slug.contains(['/', '\\']) {
+            return Err(InvalidBlogID::Separator);
+        }
+        if slug == "." || slug.contains("..") {
+            return Err(InvalidBlogID::DotSegment);
         }
+        if slug.chars().any(char::is_control) {
+            return Err(InvalidBlogID::ControlCharacter);
+        }
+        if !slug
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '~'))
+        {
+            return Err(InvalidBlogID::UnsafeCharacter);
+        }
+        Ok(BlogID { slug })
     }
 
-    pub fn load_html(&self) -> Result<Markup, io::Error> {
-        let md = std::fs::read_to_string(format!("./posts/{}.md", self.file_name))?;
-        let content = match separate_frontmatter_and_content(&md) {
-            Some((_, post_content)) => post_content,
-            None => return Err(io::ErrorKind::InvalidData.into()),
-        };
-        Ok(PreEscaped(markdown::to_html(&content)))
+    pub fn slug(&self) -> &str {
+        &self.slug
+    }
+
+    // `/blog/{slug}` with non-ASCII letters percent-encoded. Every link to a post is built here.
+    pub fn url_path(&self) -> String {
+        let mut path = String::from("/blog/");
+        for byte in self.slug.bytes() {
+            match byte.is_ascii() {
+                true => path.push(byte as char),
+                false => path.push_str(&format!("%{:02X}", byte)),
+            }
+        }
+        path
     }
 }
 
-impl ToString for BlogID {
-    fn to_string(&self) -> String {
-        self.file_name.clone()
+impl TryFrom<String> for BlogID {
+    type Error = InvalidBlogID;
+
+    fn try_from(slug: String) -> Result<Self, Self::Error> {
+        BlogID::new(slug)
     }
 }
 
+impl Display for BlogID {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.slug)
+    }
+}
+
+impl Display for InvalidBlogID {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            InvalidBlogID::Empty => "is empty",
+            InvalidBlogID::Separator => "contains a path separator",
+            InvalidBlogID::DotSegment => "is `.` or contains `..`",
+            InvalidBlogID::ControlCharacter => "contains a control character",
+            InvalidBlogID::UnsafeCharacter => {
+                "contains a character other than letters, digits, `-`, `_`, `.` and `~`"
+            }
+        })
+    }
+}
+
+impl std::error::Error for InvalidBlogID {}
+
 #[derive(PartialEq, Eq, Hash, Deserialize, Debug)]
 pub struct BlogPostInfo {
     pub title: String,
     pub tagline: String,
     pub image: Option<String>,
-    #[serde(default)]
+    // Parsed separately by `parse_frontmatter` so an unknown tag doesn't reject the whole post.
+    #[serde(skip)]
     pub tags: Vec<ContentTag>,
-    #[serde(skip, default = "SystemTime::now")]
-    pub created_at: SystemTime,
+    // `date` and `updated` accept either `2025-07-14` or a full RFC 3339 timestamp.
+    #[serde(default, deserialize_with = "deserialize_frontmatter_date")]
+    pub date: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_frontmatter_date")]
+    pub updated: Option<DateTime<Utc>>,
+    // Drafts and posts with a future `publish_at` are only visible in preview mode.
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default, deserialize_with = "deserialize_frontmatter_date")]
+    pub publish_at: Option<DateTime<Utc>>,
+    // Per-post markdown extensions, e.g. `markdown: { math: true }`.
+    #[serde(default)]
+    pub markdown: MarkdownExtensions,
+    // Slugs of the projects in `./projects/` this post is about, e.g. `projects: [fmod_gd4]`.
+    #[serde(default)]
+    pub projects: Vec<ProjectID>,
+    // The post's URL, `/blog/{slug}`, kept when the file is renamed. Defaults to the file name.
+    #[serde(default)]
+    pub slug: Option<String>,
+    // Old slugs that redirect to this post.
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    // Name of the series this post is part of, see `blog::series`.
+    #[serde(default)]
+    pub series: Option<String>,
+    // 1 for the first part. Parts without one come after those with one.
+    #[serde(default)]
+    pub series_part: Option<u32>,
+    // Force the table of contents on or off. By default it's shown for posts with enough headings.
+    #[serde(default)]
+    pub toc: Option<bool>,
+    // The markdown file in `./posts/`, without `.md`.
+    #[serde(skip)]
+    pub file_name: String,
+    // The file's canonical path, checked to be inside `./posts/` when indexing. The only path
+    // the post is ever read from.
+    #[serde(skip)]
+    pub path: PathBuf,
+    // Resolved when indexing: `date` if set, then `publish_at`, otherwise the file's modified time.
+    #[serde(skip)]
+    pub published_at: DateTime<Utc>,
+    // Counted from the post body when indexing.
+    #[serde(skip)]
+    pub word_count: usize,
+    #[serde(skip)]
+    pub reading_minutes: usize,
+}
+
+const WORDS_PER_MINUTE: usize = 200;
+
+// Words in the markdown body, ignoring tokens without letters or digits like `#`, `-` or `|`.
+fn count_words(content: &str) -> usize {
+    content
+        .split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .count()
+}
+
+impl BlogPostInfo {
+    pub fn is_published(&self, now: DateTime<Utc>) -> bool {
+        !self.draft && self.publish_at.is_none_or(|publish_at| publish_at <= now)
+    }
+
+    pub fn is_visible(&self, preview: PreviewMode) -> bool {
+        preview.0 || self.is_published(Utc::now())
+    }
+
+    // The post's markdown file, frontmatter included.
+    pub fn load_markdown(&self) -> Result<String, io::Error> {
+        std::fs::read_to_string(&self.path)
+    }
 }
 
 #[derive(Default)]
-pub struct BlogPostIndex(pub HashMap<BlogID, BlogPostInfo>);
+pub struct BlogPostIndex {
+    pub posts: HashMap<BlogID, BlogPostInfo>,
+    // Aliases and old file names, to the post they now redirect to.
+    pub redirects: HashMap<String, BlogID>,
+}
 
 impl BlogPostIndex {
     pub fn new() -> Self {
         Default::default()
     }
 
-    pub fn as_sorted_vec(&self) -> Vec<(&BlogID, &BlogPostInfo)> {
-        let mut vec = self.0.iter().collect::<Vec<(&BlogID, &BlogPostInfo)>>();
-        vec.sort_by(|a, b| b.1.created_at.cmp(&a.1.created_at));
+    // Looks up a post, hiding drafts and scheduled posts outside of preview mode.
+    pub fn get(&self, blog_id: &BlogID, preview: PreviewMode) -> Option<&BlogPostInfo> {
+        self.posts
+            .get(blog_id)
+            .filter(|post_info| post_info.is_visible(preview))
+    }
+
+    // The post an alias or old URL redirects to, if it's visible.
+    pub fn redirect(&self, slug: &str, preview: PreviewMode) -> Option<&BlogID> {
+        self.redirects
+            .get(slug)
+            .filter(|blog_id| self.get(blog_id, preview).is_some())
+    }
+
+    pub fn as_sorted_vec(&self, preview: PreviewMode) -> Vec<(&BlogID, &BlogPostInfo)> {
+        let mut vec = self
+            .posts
+            .iter()
+            .filter(|(_, post_info)| post_info.is_visible(preview))
+            .collect::<Vec<(&BlogID, &BlogPostInfo)>>();
+        // Posts published at the same time are ordered by slug, so the order doesn't change
+        // between reloads.
+        vec.sort_by(|(a_id, a), (b_id, b)| {
+            b.published_at
+                .cmp(&a.published_at)
+                .then_with(|| a_id.slug().cmp(b_id.slug()))
+        });
         vec
     }
+
+    // Visible posts about a project, newest first.
+    pub fn about_project(
+        &self,
+        project_id: &ProjectID,
+        preview: PreviewMode,
+    ) -> Vec<(&BlogID, &BlogPostInfo)> {
+        self.as_sorted_vec(preview)
+            .into_iter()
+            .filter(|(_, post_info)| post_info.projects.contains(project_id))
+            .collect()
+    }
+
+    // Visible posts in the series with this slug, in reading order. Parts without a
+    // `series_part` come last, oldest first.
+    pub fn series(&self, slug: &str, preview: PreviewMode) -> Vec<(&BlogID, &BlogPostInfo)> {
+        let mut parts: Vec<(&BlogID, &BlogPostInfo)> = self
+            .as_sorted_vec(preview)
+            .into_iter()
+            .filter(|(_, post_info)| {
+                post_info
+                    .series
+                    .as_ref()
+                    .is_some_and(|series| slugify(series) == slug)
+            })
+            .collect();
+        parts.sort_by_key(|(_, post_info)| {
+            (
+                post_info.series_part.is_none(),
+                post_info.series_part,
+                post_info.published_at,
+            )
+        });
+        parts
+    }
+
+    // The name of every series with a visible post, once per slug.
+    pub fn all_series(&self, preview: PreviewMode) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .as_sorted_vec(preview)
+            .into_iter()
+            .filter_map(|(_, post_info)| post_info.series.clone())
+            .collect();
+        names.sort_by_key(|name| slugify(name));
+        names.dedup_by_key(|name| slugify(name));
+        names
+    }
+
+    // How many visible posts carry each tag. Unused tags are missing.
+    pub fn tag_counts(&self, preview: PreviewMode) -> HashMap<ContentTag, usize> {
+        let mut counts = HashMap::new();
+        for (_, post_info) in self.as_sorted_vec(preview) {
+            for tag in &post_info.tags {
+                *counts.entry(tag.clone()).or_default() += 1;
+            }
+        }
+        counts
+    }
 }
 
-pub fn index_blog_posts() -> Result<BlogPostIndex, io::Error> {
-    let dir = std::fs::read_dir(Path::new(BLOG_POST_FOLDER_PATH))?;
-    let mut index = BlogPostIndex::new();
-    for result in dir {
-        let entry = result?;
-        let post_md = std::fs::read_to_string(entry.path())?;
-        let frontmatter_yaml = match separate_frontmatter_and_content(&post_md) {
-            Some((frontmatter, _)) => frontmatter,
-            None => return Err(io::ErrorKind::InvalidData.into()),
-        };
-        let mut post_info: BlogPostInfo = match serde_yml::from_str(&frontmatter_yaml) {
-            Ok(post_info) => post_info,
-            Err(_) => return Err(io::ErrorKind::InvalidData.into()),
+// Whether unpublished posts should be shown.
+// Extracted from a `?preview=<secret>` query parameter matching the configured preview secret.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct PreviewMode(pub bool);
+
+#[derive(Deserialize)]
+struct PreviewQuery {
+    preview: Option<String>,
+}
+
+impl PreviewMode {
+    // Query string to append to links so preview mode carries over to the next page.
+    pub fn query_suffix(&self) -> String {
+        match (self.0, &site_config().preview_secret) {
+            (true, Some(secret)) => format!("?preview={}", secret),
+            _ => String::new(),
+        }
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for PreviewMode {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let enabled = match Query::<PreviewQuery>::from_request_parts(parts, state).await {
+            Ok(Query(PreviewQuery {
+                preview: Some(secret),
+            })) => site_config().is_preview_secret(&secret),
+            _ => false,
         };
-        post_info.created_at = entry
-            .metadata()
-            .expect("Error metadata")
-            .created()
-            .expect(""); // TODO BETTER ERROR HANDLE
-        index.0.insert(
-            BlogID {
-                file_name: String::from(
-                    Path::new(&entry.file_name())
-                        .with_extension("")
-                        .to_string_lossy(),
-                ),
-            },
-            post_info,
+        Ok(PreviewMode(enabled))
+    }
+}
+
+// Indexes every markdown file in `./posts/`.
+// Broken posts are reported as diagnostics instead of failing the whole index,
+// only an unreadable posts folder is an error.
+pub fn index_blog_posts(
+    tags: &TagRegistry,
+    projects: &ProjectIndex,
+) -> Result<(BlogPostIndex, Vec<IndexDiagnostic>), io::Error> {
+    index_blog_posts_in(Path::new(BLOG_POST_FOLDER_PATH), tags, projects)
+}
+
+// `index_blog_posts` for any folder.
+pub fn index_blog_posts_in(
+    folder: &Path,
+    tags: &TagRegistry,
+    projects: &ProjectIndex,
+) -> Result<(BlogPostIndex, Vec<IndexDiagnostic>), io::Error> {
+    let folder = std::fs::canonicalize(folder)?;
+    let mut entries = std::fs::read_dir(&folder)?.collect::<Result<Vec<DirEntry>, io::Error>>()?;
+    // In file name order, so the same post wins a slug conflict every time.
+    entries.sort_by_key(DirEntry::file_name);
+    let mut index = BlogPostIndex::new();
+    let mut diagnostics = Vec::new();
+    for entry in entries {
+        let path = entry.path();
+        if path.extension().is_none_or(|extension| extension != "md") {
+            continue;
+        }
+        if let Some((blog_id, post_info)) = index_blog_post(
+            &entry,
+            &folder,
+            tags,
+            projects,
+            &mut index,
+            &mut diagnostics,
+        ) {
+            index.posts.insert(blog_id, post_info);
+        }
+    }
+    // Links to a renamed post's old file name keep working, unless something else claims it.
+    let file_name_redirects: Vec<(String, BlogID)> = index
+        .posts
+        .iter()
+        .filter(|(blog_id, post_info)| blog_id.slug != post_info.file_name)
+        .map(|(blog_id, post_info)| (post_info.file_name.clone(), blog_id.clone()))
+        .collect();
+    for (file_name, blog_id) in file_name_redirects {
+        let claimed = BlogID::new(file_name.clone())
+            .is_ok_and(|file_name_id| index.posts.contains_key(&file_name_id));
+        if !claimed {
+            index.redirects.entry(file_name).or_insert(blog_id);
+        }
+    }
+    Ok((index, diagnostics))
+}
+
+// `entry`'s canonical path, as long as it's inside `folder`. A symlink out of `./posts/` (or
+// `./projects/`) is refused rather than followed.
+pub fn resolve_post_path(folder: &Path, entry_path: &Path) -> Result<PathBuf, io::Error> {
+    let path = std::fs::canonicalize(entry_path)?;
+    match path.starts_with(folder) {
+        true => Ok(path),
+        false => Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("{} is outside {}", path.display(), folder.display()),
+        )),
+    }
+}
+
+fn index_blog_post(
+    entry: &DirEntry,
+    folder: &Path,
+    tags: &TagRegistry,
+    projects: &ProjectIndex,
+    index: &mut BlogPostIndex,
+    diagnostics: &mut Vec<IndexDiagnostic>,
+) -> Option<(BlogID, BlogPostInfo)> {
+    let file_name = entry.file_name().to_string_lossy().to_string();
+    let mut report = |line: Option<usize>, kind: IndexDiagnosticKind| {
+        diagnostics.push(IndexDiagnostic::new(&file_name, line, kind));
+    };
+    let path = match resolve_post_path(folder, &entry.path()) {
+        Ok(path) => path,
+        Err(err) => {
+            report(None, IndexDiagnosticKind::Io(err.to_string()));
+            return None;
+        }
+    };
+    let post_md = match std::fs::read_to_string(&path) {
+        Ok(post_md) => post_md,
+        Err(err) => {
+            report(None, IndexDiagnosticKind::Io(err.to_string()));
+            return None;
+        }
+    };
+    let mut frontmatter = parse_frontmatter::<BlogPostInfo>(&post_md, tags, &mut report)?;
+    // Like unknown tags, unknown projects are dropped with a warning.
+    for project_id in std::mem::take(&mut frontmatter.info.projects) {
+        if projects.get(&project_id).is_some() {
+            frontmatter.info.projects.push(project_id);
+        } else {
+            let line = frontmatter.list_item_line("projects", &project_id.slug);
+            report(line, IndexDiagnosticKind::UnknownProject(project_id.slug));
+        }
+    }
+    let file_stem = String::from(Path::new(&file_name).with_extension("").to_string_lossy());
+    let blog_id = match &frontmatter.info.slug {
+        Some(slug) => BlogID::new(slug.trim()),
+        None => BlogID::new(file_stem.clone()),
+    };
+    let blog_id = match blog_id {
+        Ok(blog_id) => blog_id,
+        Err(err) => {
+            let line = frontmatter.field_line("slug");
+            let slug = frontmatter.info.slug.clone().unwrap_or(file_stem);
+            report(line, IndexDiagnosticKind::InvalidSlug(slug, err));
+            return None;
+        }
+    };
+    // The first post to claim a slug keeps it, a later post with the same slug is skipped.
+    if let Some(other_info) = index.posts.get(&blog_id) {
+        let line = frontmatter.field_line("slug");
+        let other_file = format!("{}.md", other_info.file_name);
+        report(
+            line,
+            IndexDiagnosticKind::DuplicateSlug(blog_id.slug.clone(), other_file),
         );
+        return None;
     }
-    Ok(index)
+    for alias in std::mem::take(&mut frontmatter.info.aliases) {
+        let alias_id = match BlogID::new(alias.trim()) {
+            Ok(alias_id) => alias_id,
+            Err(err) => {
+                let line = frontmatter.list_item_line("aliases", &alias);
+                report(line, IndexDiagnosticKind::InvalidAlias(alias, err));
+                continue;
+            }
+        };
+        let alias = alias_id.slug.clone();
+        let claimed_by = index
+            .posts
+            .get(&alias_id)
+            .or_else(|| {
+                index
+                    .redirects
+                    .get(&alias)
+                    .and_then(|other_id| index.posts.get(other_id))
+            })
+            .map(|other_info| format!("{}.md", other_info.file_name))
+            .or_else(|| (alias == blog_id.slug).then(|| file_name.clone()));
+        match claimed_by {
+            Some(other_file) => {
+                let line = frontmatter.list_item_line("aliases", &alias);
+                report(line, IndexDiagnosticKind::DuplicateAlias(alias, other_file));
+            }
+            None if frontmatter.info.aliases.contains(&alias) => {}
+            None => frontmatter.info.aliases.push(alias),
+        }
+    }
+    let Frontmatter {
+        info: mut post_info,
+        tags: post_tags,
+        content,
+        ..
+    } = frontmatter;
+    post_info.tags = post_tags;
+    // A scheduled post without a `date` is dated when it goes live.
+    post_info.published_at = match post_info.date.or(post_info.publish_at) {
+        Some(date) => date,
+        None => {
+            report(None, IndexDiagnosticKind::MissingDate);
+            match entry.metadata().and_then(|metadata| metadata.modified()) {
+                Ok(modified) => modified.into(),
+                Err(err) => {
+                    report(None, IndexDiagnosticKind::Io(err.to_string()));
+                    return None;
+                }
+            }
+        }
+    };
+    post_info.word_count = count_words(&content);
+    post_info.reading_minutes = post_info.word_count.div_ceil(WORDS_PER_MINUTE).max(1);
+    post_info.file_name = file_stem;
+    post_info.path = path;
+    // A post's own slug beats an earlier post's alias.
+    if let Some(other_id) = index.redirects.remove(&blog_id.slug) {
+        if let Some(other_info) = index.posts.get(&other_id) {
+            diagnostics.push(IndexDiagnostic::new(
+                format!("{}.md", other_info.file_name),
+                None,
+                IndexDiagnosticKind::DuplicateAlias(blog_id.slug.clone(), file_name),
+            ));
+        }
+    }
+    // Only now that nothing can fail, so aliases never redirect to a post that was skipped.
+    for alias in &post_info.aliases {
+        index.redirects.insert(alias.clone(), blog_id.clone());
+    }
+    Some((blog_id, post_info))
 }
 
 pub fn get_blog_posts_modified_timestamp() -> Result<SystemTime, io::Error> {
@@ -102,14 +552,206 @@ pub fn get_blog_posts_modified_timestamp() -> Result<SystemTime, io::Error> {
     meta.modified()
 }
 
-pub fn content_tags_html(tags: &Vec<ContentTag>) -> Markup {
+pub fn content_tags_html(tags: &[ContentTag]) -> Markup {
     html! {
         @for tag in tags {
-            a href={ "/blog?filters[0]=" (remove_quotes(serde_json::to_string(tag).unwrap())) }{
-                button class="chip" {
+            a href={ "/tags/" (tag.slug()) }{
+                button class="chip" style=[tag_colour_style(tag)] {
+                    @if let Some(icon) = &tag.info().icon {
+                        i { (icon) }
+                    }
                     (tag)
                 }
             }
         }
     }
 }
+
+// Outlines a tag's chips in its `colour` from `tags.yml`.
+pub fn tag_colour_style(tag: &ContentTag) -> Option<String> {
+    tag.info()
+        .colour
+        .as_ref()
+        .map(|colour| format!("border-color: {}", colour))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blog_id_accepts_slugs() {
+        for slug in ["jem_game", "jem-game", "StrawberryLang", "v1.2", "café"] {
+            assert_eq!(BlogID::new(slug).unwrap().slug(), slug);
+        }
+    }
+
+    #[test]
+    fn blog_id_url_path_is_encoded() {
+        assert_eq!(
+            BlogID::new("jem_game").unwrap().url_path(),
+            "/blog/jem_game"
+        );
+        assert_eq!(BlogID::new("café").unwrap().url_path(), "/blog/caf%C3%A9");
+    }
+
+    #[test]
+    fn blog_id_rejects_hostile_inputs() {
+        let cases = [
+            ("", InvalidBlogID::Empty),
+            ("../Cargo.toml", InvalidBlogID::Separator),
+            ("/etc/passwd", InvalidBlogID::Separator),
+            ("posts/jem_game", InvalidBlogID::Separator),
+            ("..\\..\\secret", InvalidBlogID::Separator),
+            ("..", InvalidBlogID::DotSegment),
+            (".", InvalidBlogID::DotSegment),
+            ("jem..game", InvalidBlogID::DotSegment),
+            ("jem\0.md", InvalidBlogID::ControlCharacter),
+            ("jem\ngame", InvalidBlogID::ControlCharacter),
+            ("jem\u{7f}", InvalidBlogID::ControlCharacter),
+            ("c#-tips", InvalidBlogID::UnsafeCharacter),
+            ("why?", InvalidBlogID::UnsafeCharacter),
+            ("jem game", InvalidBlogID::UnsafeCharacter),
+            ("100%", InvalidBlogID::UnsafeCharacter),
+        ];
+        for (slug, expected) in cases {
+            assert_eq!(BlogID::new(slug), Err(expected), "{:?}", slug);
+        }
+    }
+
+    // Route parameters are deserialized, which must go through `BlogID::new` too.
+    #[test]
+    fn blog_id_deserialize_is_validated() {
+        assert!(serde_json::from_str::<BlogID>(r#""jem_game""#).is_ok());
+        assert!(serde_json::from_str::<BlogID>(r#""../../etc/passwd""#).is_err());
+        assert!(serde_json::from_str::<BlogID>(r#""..""#).is_err());
+        assert!(serde_json::from_str::<BlogID>(r#""a\u0000b""#).is_err());
+    }
+
+    #[test]
+    fn resolve_post_path_stays_in_folder() {
+        let root = std::env::temp_dir().join(format!("blog-posts-test-{}", std::process::id()));
+        let folder = root.join("posts");
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(folder.join("post.md"), "").unwrap();
+        std::fs::write(root.join("secret.md"), "").unwrap();
+        let folder = std::fs::canonicalize(&folder).unwrap();
+
+        let resolved = resolve_post_path(&folder, &folder.join("post.md")).unwrap();
+        assert!(resolved.starts_with(&folder));
+        assert!(resolve_post_path(&folder, &folder.join("../secret.md")).is_err());
+        assert!(resolve_post_path(&folder, &folder.join("missing.md")).is_err());
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(root.join("secret.md"), folder.join("link.md")).unwrap();
+            assert!(resolve_post_path(&folder, &folder.join("link.md")).is_err());
+        }
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    // Indexes `posts`, given as file names and contents, from a fresh folder named `test_name`.
+    fn index_test_posts(
+        test_name: &str,
+        posts: &[(&str, &str)],
+    ) -> (BlogPostIndex, Vec<IndexDiagnosticKind>) {
+        let folder = std::env::temp_dir().join(format!(
+            "blog-index-test-{}-{}",
+            test_name,
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&folder).unwrap();
+        for (file_name, frontmatter) in posts {
+            let post_md = format!(
+                "---\ntitle: T\ntagline: T\ndate: 2025-01-01\n{}---\nBody\n",
+                frontmatter
+            );
+            std::fs::write(folder.join(file_name), post_md).unwrap();
+        }
+        let (index, diagnostics) =
+            index_blog_posts_in(&folder, &TagRegistry::default(), &ProjectIndex::new()).unwrap();
+        std::fs::remove_dir_all(&folder).unwrap();
+        let kinds = diagnostics
+            .into_iter()
+            .map(|diagnostic| diagnostic.kind)
+            .collect();
+        (index, kinds)
+    }
+
+    fn file_name_of<'a>(index: &'a BlogPostIndex, slug: &str) -> Option<&'a str> {
+        index
+            .posts
+            .get(&BlogID::new(slug).unwrap())
+            .map(|post_info| post_info.file_name.as_str())
+    }
+
+    #[test]
+    fn duplicate_slug_keeps_first_file() {
+        let (index, diagnostics) = index_test_posts(
+            "duplicate-slug",
+            &[("a.md", "slug: same\n"), ("b.md", "slug: same\n")],
+        );
+        assert_eq!(index.posts.len(), 1);
+        assert_eq!(file_name_of(&index, "same"), Some("a"));
+        assert_eq!(
+            diagnostics,
+            [IndexDiagnosticKind::DuplicateSlug(
+                "same".into(),
+                "a.md".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn later_slug_beats_earlier_alias() {
+        let (index, diagnostics) =
+            index_test_posts("alias-slug", &[("a.md", "aliases: [b]\n"), ("b.md", "")]);
+        assert_eq!(file_name_of(&index, "b"), Some("b"));
+        assert!(!index.redirects.contains_key("b"));
+        assert_eq!(
+            diagnostics,
+            [IndexDiagnosticKind::DuplicateAlias(
+                "b".into(),
+                "b.md".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn duplicate_alias_keeps_first_post() {
+        let (index, diagnostics) = index_test_posts(
+            "alias-alias",
+            &[("a.md", "aliases: [old]\n"), ("b.md", "aliases: [old]\n")],
+        );
+        assert_eq!(index.redirects.get("old").map(BlogID::slug), Some("a"));
+        assert_eq!(
+            diagnostics,
+            [IndexDiagnosticKind::DuplicateAlias(
+                "old".into(),
+                "a.md".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn file_name_redirect_skipped_when_claimed() {
+        let (index, diagnostics) = index_test_posts(
+            "file-name-redirect",
+            &[
+                // `a.md` is now `/blog/new-a`, but `b.md` took `/blog/a`.
+                ("a.md", "slug: new-a\n"),
+                ("b.md", "slug: a\n"),
+                // `x.md` is now `/blog/new-x`, but `y.md` has `x` as an alias.
+                ("x.md", "slug: new-x\n"),
+                ("y.md", "aliases: [x]\n"),
+                // Nothing claims `/blog/z`, so it redirects.
+                ("z.md", "slug: new-z\n"),
+            ],
+        );
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        assert_eq!(file_name_of(&index, "a"), Some("b"));
+        assert!(!index.redirects.contains_key("a"));
+        assert_eq!(index.redirects.get("x").map(BlogID::slug), Some("y"));
+        assert_eq!(index.redirects.get("z").map(BlogID::slug), Some("new-z"));
+    }
+}
@@ -1,86 +1,443 @@
-use axum::{
-    extract::{Path, Query, State},
-    response::IntoResponse,
-};
-use chrono::Utc;
-use maud::{html, Markup, PreEscaped};
-use serde::Deserialize;
+use axum::{extract::State, http::StatusCode};
+use chrono::{DateTime, Utc};
+use maud::{html, Markup};
+use serde::{Deserialize, Serialize};
 use serde_qs::axum::OptionalQsQuery;
 use std::{
+    collections::HashMap,
+    fmt::Display,
     sync::{Arc, Mutex},
-    time::SystemTime,
 };
 
 use crate::{
-    blog,
-    helpers::{remove_quotes, WebPage},
-    projects::{self, ContentTag},
+    blog::{
+        self,
+        render::render_inline_markdown,
+        search::{SearchIndex, SearchQuery},
+        tag_colour_style, PreviewMode,
+    },
+    config::site_config,
+    helpers::{remove_quotes, PageMeta, WebPage},
+    tags::{ContentTag, TagRegistry},
     BlogState,
 };
 
-fn is_tag_selected(tag: &ContentTag, current_filter_set: &Option<TagFilterSet>) -> bool {
-    current_filter_set
-        .as_ref()
-        .filter(|filter_vec| filter_vec.filters.contains(&tag))
-        .is_some()
-    // current_filter_set.filters.contains(tag)
+// For `/blog` and the feeds.
+pub const BLOG_DESCRIPTION: &str =
+    "Posts about game development, programming languages and web development.";
+
+// A filter chip cycles through these states when clicked, see `filter_tag_buttons.js`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TagSelection {
+    Off,
+    Included,
+    Excluded,
+}
+
+fn is_tag_selected<S: Clone>(
+    tag: &ContentTag,
+    current_filter_set: &Option<TagFilterSet<S>>,
+) -> TagSelection {
+    match current_filter_set {
+        Some(filter_set) if filter_set.includes(tag) => TagSelection::Included,
+        Some(filter_set) if filter_set.excludes(tag) => TagSelection::Excluded,
+        _ => TagSelection::Off,
+    }
+}
+
+// How the included tags in `TagFilterSet::filters` combine.
+#[derive(Serialize, Deserialize, Default, Copy, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterMode {
+    // Posts must have every included tag.
+    #[default]
+    All,
+    // Posts must have at least one included tag.
+    Any,
 }
+pub const ALL_FILTER_MODES: [FilterMode; 2] = [FilterMode::All, FilterMode::Any];
 
-#[derive(Deserialize, Debug)]
-pub struct TagFilterSet {
+impl Display for FilterMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            FilterMode::All => "Match all",
+            FilterMode::Any => "Match any",
+        })
+    }
+}
+
+// `S` is how the list is sorted, `/projects` uses `ProjectSort` instead.
+#[derive(Deserialize, Default, Clone, Debug)]
+pub struct TagFilterSet<S = PostSort> {
+    // Ids of the included tags, see `tags.yml`.
+    #[serde(default)]
+    pub filters: Vec<String>,
     #[serde(default)]
-    pub filters: Vec<ContentTag>,
+    pub mode: FilterMode,
+    // Posts with any of these tags are left out, whatever the mode.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    // Free-text search, see `blog::search`. Empty when not searching.
+    #[serde(default)]
+    pub q: String,
+    // Unset means newest first, or best match first while searching.
+    #[serde(default)]
+    pub sort: Option<S>,
+}
+
+// Update `ALL_POST_SORTS` when this enum changes.
+#[derive(Serialize, Deserialize, Default, Copy, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum PostSort {
+    #[default]
+    Newest,
+    Oldest,
+    Longest,
+    Shortest,
+    // Only offered while searching.
+    Relevance,
+}
+pub const ALL_POST_SORTS: [PostSort; 5] = [
+    PostSort::Relevance,
+    PostSort::Newest,
+    PostSort::Oldest,
+    PostSort::Longest,
+    PostSort::Shortest,
+];
+
+impl Display for PostSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PostSort::Newest => "Newest",
+            PostSort::Oldest => "Oldest",
+            PostSort::Longest => "Longest",
+            PostSort::Shortest => "Shortest",
+            PostSort::Relevance => "Best match",
+        })
+    }
+}
+
+impl PostSort {
+    // Expects `posts` newest first, as returned by `BlogPostIndex::as_sorted_vec`, or best match
+    // first for `Relevance`.
+    pub fn sort(&self, posts: &mut [(&blog::BlogID, &blog::BlogPostInfo)]) {
+        match self {
+            PostSort::Newest | PostSort::Relevance => {}
+            PostSort::Oldest => posts.reverse(),
+            PostSort::Longest => {
+                posts.sort_by_key(|(_, post_info)| std::cmp::Reverse(post_info.word_count))
+            }
+            PostSort::Shortest => posts.sort_by_key(|(_, post_info)| post_info.word_count),
+        }
+    }
+}
+
+impl<S: Clone> TagFilterSet<S> {
+    // The same filters with ids and aliases written any way (`rust`, `godot`) swapped for the tag
+    // ids, which everything below compares exactly. Unknown ids are kept and match no tags.
+    pub fn resolved(mut self, tags: &TagRegistry) -> Self {
+        let resolve = |ids: Vec<String>| {
+            let mut resolved: Vec<String> = Vec::with_capacity(ids.len());
+            for id in ids {
+                let id = tags.resolve(&id).map_or(id, |tag| tag.id().to_string());
+                if !resolved.contains(&id) {
+                    resolved.push(id);
+                }
+            }
+            resolved
+        };
+        self.filters = resolve(std::mem::take(&mut self.filters));
+        self.exclude = resolve(std::mem::take(&mut self.exclude));
+        self
+    }
+
+    // Tags match when they include every included tag (or any of them, in `FilterMode::Any`)
+    // and none of the excluded ones.
+    pub fn matches_tags(&self, tags: &[ContentTag]) -> bool {
+        let has_tag = |id: &String| tags.iter().any(|tag| tag.id() == id);
+        let included = match self.mode {
+            FilterMode::All => self.filters.iter().all(has_tag),
+            FilterMode::Any => self.filters.is_empty() || self.filters.iter().any(has_tag),
+        };
+        included && !self.exclude.iter().any(has_tag)
+    }
+
+    pub fn includes(&self, tag: &ContentTag) -> bool {
+        self.filters.iter().any(|id| id == tag.id())
+    }
+
+    pub fn excludes(&self, tag: &ContentTag) -> bool {
+        self.exclude.iter().any(|id| id == tag.id())
+    }
+
+    // This filter set with `tag` also included, as if its chip were clicked.
+    pub fn with_included(&self, tag: &ContentTag) -> TagFilterSet<S> {
+        let mut filter_set = self.clone();
+        filter_set.exclude.retain(|id| id != tag.id());
+        if !filter_set.includes(tag) {
+            filter_set.filters.push(tag.id().to_string());
+        }
+        filter_set
+    }
+
+    // Whether including another tag can only add to the list, rather than narrow it.
+    pub fn adds_results(&self) -> bool {
+        self.mode == FilterMode::Any && !self.filters.is_empty()
+    }
+
+    pub fn has_tag_filters(&self) -> bool {
+        !self.filters.is_empty() || !self.exclude.is_empty()
+    }
+
+    // "Rust or Game, not C++", for feed titles.
+    pub fn describe(&self, tags: &TagRegistry) -> String {
+        let separator = match self.mode {
+            FilterMode::All => ", ",
+            FilterMode::Any => " or ",
+        };
+        let included = self.filters.iter().map(|id| tags.name_of(id));
+        let excluded = self
+            .exclude
+            .iter()
+            .map(|id| format!("not {}", tags.name_of(id)));
+        let included = included.collect::<Vec<String>>().join(separator);
+        std::iter::once(included)
+            .filter(|included| !included.is_empty())
+            .chain(excluded)
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    pub fn is_searching(&self) -> bool {
+        !self.q.trim().is_empty()
+    }
+}
+
+impl TagFilterSet {
+    pub fn matches(&self, post_info: &blog::BlogPostInfo) -> bool {
+        self.matches_tags(&post_info.tags)
+    }
+
+    // The sort the list is actually shown in.
+    pub fn effective_sort(&self) -> PostSort {
+        match (self.sort, self.is_searching()) {
+            (Some(PostSort::Relevance), false) | (None, false) => PostSort::Newest,
+            (None, true) => PostSort::Relevance,
+            (Some(sort), _) => sort,
+        }
+    }
+
+    // The same `filters[n]=Tag` notation `filter_tag_buttons.js` builds.
+    pub fn to_query_string(&self) -> String {
+        tag_query_params("filters", &self.filters)
+            .chain(tag_query_params("exclude", &self.exclude))
+            .chain((self.mode != FilterMode::default()).then(|| {
+                format!(
+                    "mode={}",
+                    remove_quotes(serde_json::to_string(&self.mode).unwrap())
+                )
+            }))
+            .chain(self.is_searching().then(|| {
+                serde_qs::to_string(&SearchQuery {
+                    q: self.q.trim().to_string(),
+                })
+                .unwrap()
+            }))
+            .chain(self.sort.map(|sort| {
+                format!(
+                    "sort={}",
+                    remove_quotes(serde_json::to_string(&sort).unwrap())
+                )
+            }))
+            .collect::<Vec<String>>()
+            .join("&")
+    }
+}
+
+// For every tag that isn't filtered on yet, how many of `items` would be listed if it were
+// included, or in `FilterMode::Any` once a tag is included, how many more would be listed (see
+// `TagFilterSet::adds_results`). `items` are the tags of each post or project. Tags none of them
+// carry count as zero. Tags already in the filter set are left out.
+pub fn tag_result_counts<S: Clone>(
+    items: &[&[ContentTag]],
+    filter_set: &TagFilterSet<S>,
+    tags: &TagRegistry,
+) -> HashMap<ContentTag, usize> {
+    let current_count = match filter_set.adds_results() {
+        true => items
+            .iter()
+            .filter(|item_tags| filter_set.matches_tags(item_tags))
+            .count(),
+        false => 0,
+    };
+    tags.all()
+        .iter()
+        .filter(|tag| !filter_set.includes(tag) && !filter_set.excludes(tag))
+        .map(|tag| {
+            if !items.iter().any(|item_tags| item_tags.contains(tag)) {
+                return (tag.clone(), 0);
+            }
+            let with_tag = filter_set.with_included(tag);
+            let count = items
+                .iter()
+                .filter(|item_tags| with_tag.matches_tags(item_tags))
+                .count();
+            (tag.clone(), count.saturating_sub(current_count))
+        })
+        .collect()
+}
+
+// The tag and mode chips above a filtered list, see `filter_tag_buttons.js`.
+pub fn tag_filter_chips<S: Clone>(
+    current_filter_set: &Option<TagFilterSet<S>>,
+    tag_counts: &HashMap<ContentTag, usize>,
+    tags: &TagRegistry,
+) -> Markup {
+    let adds_results = current_filter_set
+        .as_ref()
+        .is_some_and(|filter_set| filter_set.adds_results());
+    html! {
+        @for tag in tags.all() {
+            @let selection = is_tag_selected(tag, current_filter_set);
+            @let count = tag_counts.get(tag).copied();
+            // Chips that would lead to an empty list, or add nothing to it, are hidden.
+            @if count != Some(0) {
+                button .chip
+                    .primary-container[selection == TagSelection::Included]
+                    .error-container[selection == TagSelection::Excluded]
+                    style=[(selection == TagSelection::Off).then(|| tag_colour_style(tag)).flatten()]
+                    #filter_tag data-tag=(tag.id()) {
+                    @match selection {
+                        TagSelection::Included => i { "check" },
+                        TagSelection::Excluded => i { "block" },
+                        TagSelection::Off => @if let Some(icon) = &tag.info().icon {
+                            i { (icon) }
+                        },
+                    }
+                    span { (tag) }
+                    @if let Some(count) = count {
+                        span .tag-count {
+                            @if adds_results { "+" }
+                            (count)
+                        }
+                    }
+                }
+            }
+        }
+        div .space {}
+        @let mode = current_filter_set.as_ref().map_or(FilterMode::default(), |filter_set| filter_set.mode);
+        @for filter_mode in &ALL_FILTER_MODES {
+            @let mode_string = remove_quotes(serde_json::to_string(filter_mode).unwrap());
+            button .chip .primary-container[*filter_mode == mode] #filter_mode data-mode=(mode_string) {
+                (filter_mode)
+            }
+        }
+    }
+}
+
+// `name[0]=Tag&name[1]=Tag`, split into parameters.
+fn tag_query_params<'a>(name: &'a str, ids: &'a [String]) -> impl Iterator<Item = String> + 'a {
+    ids.iter()
+        .enumerate()
+        .map(move |(i, id)| format!("{}[{}]={}", name, i, id))
 }
 
-fn get_blog_post_date_str(post_created_at: SystemTime) -> String {
-    let date: chrono::DateTime<Utc> = post_created_at.into();
+pub fn get_blog_post_date_str(date: DateTime<Utc>) -> String {
     date.format("%a %B %d, %Y").to_string()
 }
 
+// "1250 words · 7 min read"
+pub fn reading_time_markup(post_info: &blog::BlogPostInfo) -> Markup {
+    html! {
+        (post_info.word_count)
+        @if post_info.word_count == 1 { " word" } @else { " words" }
+        " · " (post_info.reading_minutes) " min read"
+    }
+}
+
+// "Mon July 14, 2025", with an "Updated on" suffix when the post has been revised.
+pub fn blog_post_dates_markup(post_info: &blog::BlogPostInfo) -> Markup {
+    html! {
+        (get_blog_post_date_str(post_info.published_at))
+        @if let Some(updated) = post_info.updated {
+            " · Updated on " (get_blog_post_date_str(updated))
+        }
+    }
+}
+
 pub async fn blog_post_list_page(
-    query: OptionalQsQuery<TagFilterSet>,
+    OptionalQsQuery(query): OptionalQsQuery<TagFilterSet>,
+    preview: PreviewMode,
     state: State<Arc<Mutex<BlogState>>>,
 ) -> WebPage {
+    let q = query
+        .as_ref()
+        .map_or(String::new(), |filter_set| filter_set.q.clone());
     WebPage {
         title: "Jordi's Blog Posts".into(),
         content: html! {
             div {
                 h4 .large { "Blog Posts" }
                 div .medium-space {}
-                (blog_post_list_items(query, state).await)
+                // Outside the refreshed list so it keeps focus while typing.
+                form #post_search_form action="/blog" method="get" {
+                    div .field .large .prefix .round .fill {
+                        i { "search" }
+                        input #post_search type="search" name="q" value=(q) placeholder="Search posts" {}
+                    }
+                    @if let (true, Some(secret)) = (preview.0, &site_config().preview_secret) {
+                        input type="hidden" name="preview" value=(secret) {}
+                    }
+                }
+                div data-list-url="/blog_post_list" {
+                    (blog_post_list_items(OptionalQsQuery(query), preview, state).await)
+                }
             }
         },
         page_type: crate::helpers::PageType::Blog,
+        status: StatusCode::OK,
+        meta: PageMeta::new(BLOG_DESCRIPTION, "/blog"),
     }
 }
 
-fn get_posts_list_markup(posts: Vec<(&blog::BlogID, &blog::BlogPostInfo)>) -> Markup {
+// `search` is the index and query to show match snippets for, while searching.
+pub fn get_posts_list_markup(
+    posts: Vec<(&blog::BlogID, &blog::BlogPostInfo)>,
+    preview: PreviewMode,
+    search: Option<(&SearchIndex, &str)>,
+) -> Markup {
     html! {
         ul .list .border {
             @for (id, post_info) in posts {
                 li {
                         div class="max" {
-                            a href=(format!("blog/{}", id.file_name)) {
+                            a href=(format!("{}{}", id.url_path(), preview.query_suffix())) {
                                 h6 class="large" {
                                     {(post_info.title)}
                                 }
+                                @if post_info.draft {
+                                    span .chip .error-container { "Draft" }
+                                } @else if !post_info.is_published(Utc::now()) {
+                                    span .chip .tertiary-container { "Scheduled" }
+                                }
                                 p {
-                                    (post_info.tagline)
+                                    (render_inline_markdown(&post_info.tagline))
                                 }
                             }
+                            @if let Some((search_index, query)) = search {
+                                p .search-snippet { (search_index.snippet(id, query)) }
+                            }
                             div {
                                 @for tag in &post_info.tags {
-                                    @let tag_string = {
-                                        let t = serde_json::to_string(tag).unwrap();
-                                        t[1..t.len()-1].to_string()
-                                    };
-                                    button .chip #filter_tag data-tag=(tag_string) {
+                                    button .chip #filter_tag data-tag=(tag.id()) style=[tag_colour_style(tag)] {
                                         (tag)
                                     }
                                 }
                             }
                             p {
-                                (get_blog_post_date_str(post_info.created_at))
+                                (blog_post_dates_markup(post_info))
+                                " · "
+                                (reading_time_markup(post_info))
                             }
                     }
                 }
@@ -91,35 +448,48 @@ fn get_posts_list_markup(posts: Vec<(&blog::BlogID, &blog::BlogPostInfo)>) -> Ma
 
 pub async fn blog_post_list_items(
     OptionalQsQuery(current_filter_set): OptionalQsQuery<TagFilterSet>,
+    preview: PreviewMode,
     State(state_mutex): State<Arc<Mutex<BlogState>>>,
 ) -> Markup {
     let mut state = state_mutex.lock().unwrap();
-    if state.is_dirty() {
-        *state = match BlogState::load() {
-            Ok(blog_state) => blog_state,
-            Err(err) => todo!(),
-        }
-    }
-    // println!("{:?}");
-    let posts: Vec<(&blog::BlogID, &blog::BlogPostInfo)> = match current_filter_set {
-        Some(ref filter_set) => state
-            .index
-            .as_sorted_vec()
+    state.refresh();
+    let state = &*state;
+    let current_filter_set = current_filter_set.map(|filter_set| filter_set.resolved(&state.tags));
+    let filter_set = current_filter_set.as_ref();
+    let query = filter_set
+        .filter(|filter_set| filter_set.is_searching())
+        .map(|filter_set| filter_set.q.trim());
+    let mut posts: Vec<(&blog::BlogID, &blog::BlogPostInfo)> = match query {
+        // Search results come best match first.
+        Some(query) => state
+            .search
+            .search(query)
             .into_iter()
-            .filter(|(_, post_info)| {
-                println!("{:?} {:?}", filter_set.filters, post_info.title);
-                for filter in &filter_set.filters {
-                    if !post_info.tags.contains(&filter) {
-                        println!("{:?} {:?}, tag NO CONTAIn", filter_set.filters, filter);
-                        return false;
-                    }
-                    println!("{:?} {:?}, tag YES", filter_set.filters, filter);
-                }
-                true
+            .filter_map(|result| {
+                state
+                    .index
+                    .get(result.blog_id, preview)
+                    .map(|post_info| (result.blog_id, post_info))
             })
             .collect(),
-        None => state.index.as_sorted_vec(),
+        None => state.index.as_sorted_vec(preview),
     };
+    let post_tags: Vec<&[ContentTag]> = posts
+        .iter()
+        .map(|(_, post_info)| post_info.tags.as_slice())
+        .collect();
+    let tag_counts = tag_result_counts(
+        &post_tags,
+        filter_set.unwrap_or(&TagFilterSet::default()),
+        &state.tags,
+    );
+    posts
+        .retain(|(_, post_info)| filter_set.is_none_or(|filter_set| filter_set.matches(post_info)));
+    let sort = filter_set.map_or(PostSort::default(), TagFilterSet::effective_sort);
+    if query.is_some() && sort != PostSort::Relevance {
+        posts.sort_by_key(|(_, post_info)| std::cmp::Reverse(post_info.published_at));
+    }
+    sort.sort(&mut posts);
     let posts_markup: Markup = 'check_post_empty_state: {
         if posts.is_empty() {
             break 'check_post_empty_state html! {
@@ -127,25 +497,36 @@ pub async fn blog_post_list_items(
                     div  {
                         i .extra { "search" }
                         h2 { "No Posts Found" }
-                        h4 { "Try removing some filters." }
+                        @if query.is_some() {
+                            h4 { "Try a different search or removing some filters." }
+                        } @else {
+                            h4 { "Try removing some filters." }
+                        }
                     }
                 }
             };
         }
-        break 'check_post_empty_state get_posts_list_markup(posts);
+        break 'check_post_empty_state get_posts_list_markup(
+            posts,
+            preview,
+            query.map(|query| (&state.search, query)),
+        );
     };
     html! {
         div #blog_posts {
             article .border {
                 h6 { "Filters" }
                 div .space {}
-                @for tag in &projects::ALL_CONTENT_TAGS {
-                    @let selected = is_tag_selected(tag, &current_filter_set);
-                    @let tag_string = {
-                        remove_quotes(serde_json::to_string(tag).unwrap())
-                    };
-                    button .chip .primary-container[selected] #filter_tag data-tag=(tag_string) {
-                        (tag)
+                (tag_filter_chips(&current_filter_set, &tag_counts, &state.tags))
+                div .space {}
+                h6 { "Sort" }
+                div .space {}
+                @for post_sort in &ALL_POST_SORTS {
+                    @if *post_sort != PostSort::Relevance || query.is_some() {
+                        @let sort_string = remove_quotes(serde_json::to_string(post_sort).unwrap());
+                        button .chip .primary-container[*post_sort == sort] #sort_posts data-sort=(sort_string) {
+                            (post_sort)
+                        }
                     }
                 }
             }
@@ -153,3 +534,128 @@ pub async fn blog_post_list_items(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Four tags, `d` on nothing, and posts tagged `a`, `a b`, `b c` and `c`.
+    fn test_tags() -> TagRegistry {
+        let yaml =
+            "- {id: a, name: A}\n- {id: b, name: B}\n- {id: c, name: C}\n- {id: d, name: D}\n";
+        TagRegistry::parse(yaml, &mut Vec::new())
+    }
+
+    fn test_items(tags: &TagRegistry) -> Vec<Vec<ContentTag>> {
+        [vec!["a"], vec!["a", "b"], vec!["b", "c"], vec!["c"]]
+            .into_iter()
+            .map(|ids| {
+                ids.into_iter()
+                    .map(|id| tags.resolve(id).unwrap().clone())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn counts(filter_set: &TagFilterSet, tags: &TagRegistry) -> Vec<(String, usize)> {
+        let items = test_items(tags);
+        let items: Vec<&[ContentTag]> = items.iter().map(Vec::as_slice).collect();
+        let counts = tag_result_counts(&items, filter_set, tags);
+        let mut counts: Vec<(String, usize)> = counts
+            .into_iter()
+            .map(|(tag, count)| (tag.id().to_string(), count))
+            .collect();
+        counts.sort();
+        counts
+    }
+
+    fn filter_set(mode: FilterMode, filters: &[&str], exclude: &[&str]) -> TagFilterSet {
+        TagFilterSet {
+            filters: filters.iter().map(|id| id.to_string()).collect(),
+            mode,
+            exclude: exclude.iter().map(|id| id.to_string()).collect(),
+            ..TagFilterSet::default()
+        }
+    }
+
+    fn expected(counts: &[(&str, usize)]) -> Vec<(String, usize)> {
+        counts
+            .iter()
+            .map(|(id, count)| (id.to_string(), *count))
+            .collect()
+    }
+
+    #[test]
+    fn counts_with_no_filters() {
+        let tags = test_tags();
+        let filter_set = filter_set(FilterMode::All, &[], &[]);
+        assert_eq!(
+            counts(&filter_set, &tags),
+            expected(&[("a", 2), ("b", 2), ("c", 2), ("d", 0)])
+        );
+    }
+
+    #[test]
+    fn counts_match_all() {
+        let tags = test_tags();
+        let filter_set = filter_set(FilterMode::All, &["a"], &[]);
+        assert_eq!(
+            counts(&filter_set, &tags),
+            expected(&[("b", 1), ("c", 0), ("d", 0)])
+        );
+    }
+
+    // Once a tag is included, another one can only add posts.
+    #[test]
+    fn counts_match_any_are_added_posts() {
+        let tags = test_tags();
+        let filter_set = filter_set(FilterMode::Any, &["a"], &[]);
+        assert!(filter_set.adds_results());
+        assert_eq!(
+            counts(&filter_set, &tags),
+            expected(&[("b", 1), ("c", 2), ("d", 0)])
+        );
+        // With nothing included yet, the first tag narrows the list like in `FilterMode::All`.
+        let filter_set = self::filter_set(FilterMode::Any, &[], &[]);
+        assert!(!filter_set.adds_results());
+        assert_eq!(
+            counts(&filter_set, &tags),
+            expected(&[("a", 2), ("b", 2), ("c", 2), ("d", 0)])
+        );
+    }
+
+    #[test]
+    fn counts_leave_out_excluded_posts() {
+        let tags = test_tags();
+        let filter_set = filter_set(FilterMode::All, &[], &["b"]);
+        assert_eq!(
+            counts(&filter_set, &tags),
+            expected(&[("a", 1), ("c", 1), ("d", 0)])
+        );
+        let filter_set = self::filter_set(FilterMode::Any, &["a"], &["b"]);
+        assert_eq!(counts(&filter_set, &tags), expected(&[("c", 1), ("d", 0)]));
+    }
+
+    #[test]
+    fn chips_hide_zero_counts() {
+        let tags = test_tags();
+        let items = test_items(&tags);
+        let items: Vec<&[ContentTag]> = items.iter().map(Vec::as_slice).collect();
+        let chips = |filter_set: TagFilterSet| {
+            let counts = tag_result_counts(&items, &filter_set, &tags);
+            tag_filter_chips(&Some(filter_set), &counts, &tags).into_string()
+        };
+
+        let html = chips(filter_set(FilterMode::All, &["a"], &[]));
+        // The included tag is shown without a count.
+        assert!(html.contains(r#"data-tag="a""#));
+        assert!(html.contains(r#"data-tag="b""#));
+        assert!(!html.contains(r#"data-tag="c""#));
+        assert!(!html.contains(r#"data-tag="d""#));
+
+        let html = chips(filter_set(FilterMode::Any, &["a"], &[]));
+        assert!(html.contains(r#"<span class="tag-count">+1</span>"#));
+        assert!(html.contains(r#"<span class="tag-count">+2</span>"#));
+        assert!(!html.contains(r#"data-tag="d""#));
+    }
+}
@@ -0,0 +1,182 @@
+// Related posts and previous/next links, shown below each post.
+// Relatedness is worked out in `BlogState::load` from shared tags and how similar the posts' text
+// is, using the term weights of the `SearchIndex`.
+
+use std::collections::HashMap;
+
+use maud::{html, Markup};
+
+use crate::blog::{
+    content_tags_html, page::blog_post_dates_markup, render::render_inline_markdown,
+    search::SearchIndex, BlogID, BlogPostIndex, BlogPostInfo, PreviewMode,
+};
+
+// How many related posts are shown below a post.
+pub const RELATED_POST_COUNT: usize = 3;
+// Shared tags and text similarity both score between 0 and 1, tags count for a bit more.
+const TAG_WEIGHT: f32 = 0.6;
+const TEXT_WEIGHT: f32 = 0.4;
+// Posts scoring less than this aren't related enough to suggest.
+const MIN_RELATED_SCORE: f32 = 0.1;
+
+// For every post, the other posts best match first. Drafts are included so preview mode shows
+// them too, `get` hides them otherwise.
+#[derive(Default)]
+pub struct RelatedPosts(HashMap<BlogID, Vec<BlogID>>);
+
+impl RelatedPosts {
+    pub fn build(index: &BlogPostIndex, search: &SearchIndex) -> Self {
+        let term_weights = search.term_weights();
+        let norms: HashMap<&BlogID, f32> = term_weights
+            .iter()
+            .map(|(blog_id, weights)| {
+                (
+                    *blog_id,
+                    weights
+                        .values()
+                        .map(|weight| weight * weight)
+                        .sum::<f32>()
+                        .sqrt(),
+                )
+            })
+            .collect();
+        // Cosine similarity of the term weights.
+        let text_similarity = |a: &BlogID, b: &BlogID| -> f32 {
+            let (Some(a_weights), Some(b_weights)) = (term_weights.get(a), term_weights.get(b))
+            else {
+                return 0.0;
+            };
+            let dot: f32 = a_weights
+                .iter()
+                .filter_map(|(term, a_weight)| {
+                    b_weights.get(term).map(|b_weight| a_weight * b_weight)
+                })
+                .sum();
+            let norm = norms[a] * norms[b];
+            match norm > 0.0 {
+                true => dot / norm,
+                false => 0.0,
+            }
+        };
+        // Shared tags over all tags of either post.
+        let tag_similarity = |a: &BlogPostInfo, b: &BlogPostInfo| -> f32 {
+            let shared = a.tags.iter().filter(|tag| b.tags.contains(tag)).count();
+            let total = a.tags.len() + b.tags.len() - shared;
+            match total {
+                0 => 0.0,
+                total => shared as f32 / total as f32,
+            }
+        };
+        let mut related = HashMap::new();
+        for (blog_id, post_info) in &index.posts {
+            let mut scores: Vec<(&BlogID, f32)> = index
+                .posts
+                .iter()
+                .filter(|(other_id, _)| *other_id != blog_id)
+                .map(|(other_id, other_info)| {
+                    let score = TAG_WEIGHT * tag_similarity(post_info, other_info)
+                        + TEXT_WEIGHT * text_similarity(blog_id, other_id);
+                    (other_id, score)
+                })
+                .filter(|(_, score)| *score >= MIN_RELATED_SCORE)
+                .collect();
+            scores.sort_by(|(a_id, a), (b_id, b)| {
+                b.total_cmp(a).then_with(|| a_id.slug().cmp(b_id.slug()))
+            });
+            related.insert(
+                blog_id.clone(),
+                scores
+                    .into_iter()
+                    .map(|(other_id, _)| other_id.clone())
+                    .collect(),
+            );
+        }
+        RelatedPosts(related)
+    }
+
+    // Up to `RELATED_POST_COUNT` visible posts related to `blog_id`, best match first.
+    pub fn get<'a>(
+        &self,
+        blog_id: &BlogID,
+        index: &'a BlogPostIndex,
+        preview: PreviewMode,
+    ) -> Vec<(&'a BlogID, &'a BlogPostInfo)> {
+        self.0
+            .get(blog_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|related_id| index.posts.get_key_value(related_id))
+            .filter(|(_, post_info)| post_info.is_visible(preview))
+            .take(RELATED_POST_COUNT)
+            .collect()
+    }
+}
+
+// The posts published just before and after a post, as listed by `as_sorted_vec`.
+pub struct AdjacentPosts<'a> {
+    pub previous: Option<(&'a BlogID, &'a BlogPostInfo)>,
+    pub next: Option<(&'a BlogID, &'a BlogPostInfo)>,
+}
+
+pub fn adjacent_posts<'a>(
+    blog_id: &BlogID,
+    index: &'a BlogPostIndex,
+    preview: PreviewMode,
+) -> AdjacentPosts<'a> {
+    let posts = index.as_sorted_vec(preview);
+    let position = posts.iter().position(|(id, _)| *id == blog_id);
+    AdjacentPosts {
+        // Newest first, so the previous post comes after this one.
+        previous: position.and_then(|i| posts.get(i + 1).copied()),
+        next: position
+            .and_then(|i| i.checked_sub(1))
+            .and_then(|i| posts.get(i).copied()),
+    }
+}
+
+pub fn related_posts_markup(
+    blog_id: &BlogID,
+    index: &BlogPostIndex,
+    related: &RelatedPosts,
+    preview: PreviewMode,
+) -> Markup {
+    let related_posts = related.get(blog_id, index, preview);
+    let AdjacentPosts { previous, next } = adjacent_posts(blog_id, index, preview);
+    html! {
+        div .large-space {}
+        @if !related_posts.is_empty() {
+            h5 { "Related Posts" }
+            div .space {}
+            div .grid {
+                @for (related_id, post_info) in &related_posts {
+                    div .s12 .m6 .l4 {
+                        article .border .round {
+                            a href=(format!("{}{}", related_id.url_path(), preview.query_suffix())) {
+                                h6 { (post_info.title) }
+                                p { (render_inline_markdown(&post_info.tagline)) }
+                            }
+                            div { (content_tags_html(&post_info.tags)) }
+                            p .small-text { (blog_post_dates_markup(post_info)) }
+                        }
+                    }
+                }
+            }
+            div .space {}
+        }
+        nav .post-navigation {
+            @if let Some((previous_id, previous_info)) = previous {
+                a .button .border href=(format!("{}{}", previous_id.url_path(), preview.query_suffix())) {
+                    i { "arrow_back" }
+                    span { (previous_info.title) }
+                }
+            }
+            div .max {}
+            @if let Some((next_id, next_info)) = next {
+                a .button .border href=(format!("{}{}", next_id.url_path(), preview.query_suffix())) {
+                    span { (next_info.title) }
+                    i { "arrow_forward" }
+                }
+            }
+        }
+    }
+}
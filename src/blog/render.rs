@@ -0,0 +1,447 @@
+// Markdown to HTML for blog posts.
+// Every page, feed and list that shows post content goes through `render_markdown`, so they all
+// get the same extensions and the same highlighting.
+//
+// `markdown::to_html_with_options` does the conversion, then its plain `<pre><code>` blocks are
+// swapped for syntax highlighted ones. The fence language and meta come from the mdast, each fence
+// is tagged with a marker so its block can be found in the HTML.
+
+use std::collections::HashSet;
+
+use markdown::{
+    mdast::{Code, Node},
+    CompileOptions, Constructs, Options, ParseOptions,
+};
+use maud::{html, Markup, PreEscaped};
+use serde::Deserialize;
+
+use crate::{
+    blog::highlight::{highlight_code_block, CodeBlockOptions},
+    config::site_config,
+    helpers::LAYOUT_IDS,
+};
+
+const CODE_BLOCK_START: &str = "<pre><code";
+const CODE_BLOCK_END: &str = "</code></pre>";
+
+// Markdown extensions. Set site-wide under `markdown:` in `site.yml`, and per post under
+// `markdown:` in the frontmatter. Unset fields fall back to the site's setting, then the default.
+#[derive(Deserialize, Default, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[serde(default)]
+pub struct MarkdownExtensions {
+    // Tables, strikethrough, task lists, autolinks and footnotes. On by default.
+    pub gfm: Option<bool>,
+    // `$inline$` and `$$` block math, rendered client-side with KaTeX. Off by default.
+    pub math: Option<bool>,
+    // Pass raw HTML through instead of escaping it. Off by default.
+    pub html: Option<bool>,
+}
+
+impl MarkdownExtensions {
+    // Fills unset fields from `defaults`.
+    pub fn or(self, defaults: MarkdownExtensions) -> Self {
+        Self {
+            gfm: self.gfm.or(defaults.gfm),
+            math: self.math.or(defaults.math),
+            html: self.html.or(defaults.html),
+        }
+    }
+
+    // Post settings, falling back to the site's.
+    pub fn resolve(self) -> Self {
+        self.or(site_config().markdown)
+    }
+
+    pub fn gfm_enabled(&self) -> bool {
+        self.gfm.unwrap_or(true)
+    }
+
+    pub fn math_enabled(&self) -> bool {
+        self.math.unwrap_or(false)
+    }
+
+    pub fn html_enabled(&self) -> bool {
+        self.html.unwrap_or(false)
+    }
+
+    fn to_options(self) -> Options {
+        let (constructs, compile) = match self.gfm_enabled() {
+            true => (Constructs::gfm(), CompileOptions::gfm()),
+            false => (Constructs::default(), CompileOptions::default()),
+        };
+        Options {
+            parse: ParseOptions {
+                constructs: Constructs {
+                    // Posts can be rendered with their frontmatter still attached.
+                    frontmatter: true,
+                    math_flow: self.math_enabled(),
+                    math_text: self.math_enabled(),
+                    ..constructs
+                },
+                ..ParseOptions::default()
+            },
+            compile: CompileOptions {
+                allow_dangerous_html: self.html_enabled(),
+                ..compile
+            },
+        }
+    }
+}
+
+// A heading in a rendered post, used to build the table of contents.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Heading {
+    pub level: usize,
+    // Slugified, unique within the post.
+    pub id: String,
+    pub text: String,
+}
+
+pub struct RenderedMarkdown {
+    pub html: Markup,
+    pub headings: Vec<Heading>,
+}
+
+// Renders a post's markdown, with or without its frontmatter.
+pub fn render_markdown(content: &str, extensions: MarkdownExtensions) -> RenderedMarkdown {
+    let extensions = extensions.resolve();
+    let options = extensions.to_options();
+    let mdast = markdown::to_mdast(content, &options.parse)
+        .inspect_err(|err| eprintln!("Error parsing markdown for highlighting: {}", err))
+        .ok();
+    let (marked, code_blocks) = match &mdast {
+        Some(mdast) => mark_code_blocks(content, mdast),
+        None => (content.to_string(), Vec::new()),
+    };
+    let html = match markdown::to_html_with_options(&marked, &options) {
+        Ok(html) => highlight_code_blocks(&html, &code_blocks, extensions),
+        Err(err) => {
+            eprintln!("Error rendering markdown: {}", err);
+            markdown::to_html(content)
+        }
+    };
+    let (html, headings) = add_heading_anchors(&html);
+    RenderedMarkdown {
+        html: PreEscaped(html),
+        headings,
+    }
+}
+
+// The readable text of a post without markup, for search.
+pub fn render_plain_text(content: &str, extensions: MarkdownExtensions) -> String {
+    let options = extensions.resolve().to_options();
+    let mut text = String::with_capacity(content.len());
+    match markdown::to_mdast(content, &options.parse) {
+        Ok(mdast) => collect_text(&mdast, &mut text),
+        Err(err) => eprintln!("Error parsing markdown for plain text: {}", err),
+    }
+    text
+}
+
+fn collect_text(node: &Node, text: &mut String) {
+    match node {
+        Node::Text(markdown::mdast::Text { value, .. })
+        | Node::InlineCode(markdown::mdast::InlineCode { value, .. })
+        | Node::Code(Code { value, .. }) => {
+            text.push_str(value);
+            text.push(' ');
+        }
+        _ => {}
+    }
+    for child in node.children().into_iter().flatten() {
+        collect_text(child, text);
+    }
+}
+
+fn collect_inline_text(node: &Node, text: &mut String) {
+    match node {
+        Node::Text(markdown::mdast::Text { value, .. })
+        | Node::InlineCode(markdown::mdast::InlineCode { value, .. }) => text.push_str(value),
+        _ => {}
+    }
+    for child in node.children().into_iter().flatten() {
+        collect_inline_text(child, text);
+    }
+}
+
+// A single line such as a tagline as plain text, for meta tags. Unlike `render_plain_text`,
+// inline code and links join the text around them without extra spaces.
+pub fn inline_plain_text(content: &str) -> String {
+    let options = MarkdownExtensions::default().resolve().to_options();
+    let mut text = String::with_capacity(content.len());
+    match markdown::to_mdast(content, &options.parse) {
+        Ok(mdast) => collect_inline_text(&mdast, &mut text),
+        Err(_) => text.push_str(content),
+    }
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+// Renders a single line such as a tagline, without the wrapping paragraph.
+pub fn render_inline_markdown(content: &str) -> Markup {
+    let html = render_markdown(content, MarkdownExtensions::default())
+        .html
+        .into_string();
+    let html = html.trim();
+    match html
+        .strip_prefix("<p>")
+        .and_then(|html| html.strip_suffix("</p>"))
+    {
+        Some(inline) if !inline.contains("<p>") => PreEscaped(inline.to_string()),
+        _ => PreEscaped(html.to_string()),
+    }
+}
+
+// Fenced code blocks get their info string swapped for a numbered marker language before
+// rendering, so every `<pre><code>` can be matched back to its mdast node. Matching by order
+// doesn't work, footnote definitions stay where they were written in the mdast but are rendered at
+// the end of the page.
+const CODE_BLOCK_MARKER: &str = "x-highlight-";
+
+fn collect_code_blocks<'a>(node: &'a Node, code_blocks: &mut Vec<&'a Code>) {
+    if let Node::Code(code) = node {
+        code_blocks.push(code);
+    }
+    for child in node.children().into_iter().flatten() {
+        collect_code_blocks(child, code_blocks);
+    }
+}
+
+// The source with marked fences, and the fenced code blocks in marker order.
+// Indented code blocks start at their indentation rather than a fence, and are left as they are.
+fn mark_code_blocks<'a>(content: &str, mdast: &'a Node) -> (String, Vec<&'a Code>) {
+    let mut code_blocks = Vec::new();
+    collect_code_blocks(mdast, &mut code_blocks);
+    code_blocks.sort_by_key(|code| code.position.as_ref().map(|position| position.start.offset));
+    let mut marked = String::with_capacity(content.len());
+    let mut fenced = Vec::new();
+    let mut copied = 0;
+    for code in code_blocks {
+        let Some(start) = code.position.as_ref().map(|position| position.start.offset) else {
+            continue;
+        };
+        let line = &content[start..];
+        let fence_len = match line.chars().next() {
+            Some(fence @ ('`' | '~')) => line.len() - line.trim_start_matches(fence).len(),
+            _ => 0,
+        };
+        if fence_len < 3 {
+            continue;
+        }
+        let info_start = start + fence_len;
+        let info_end = info_start
+            + content[info_start..]
+                .find(['\r', '\n'])
+                .unwrap_or(content.len() - info_start);
+        marked.push_str(&content[copied..info_start]);
+        marked.push_str(&format!("{}{}", CODE_BLOCK_MARKER, fenced.len()));
+        copied = info_end;
+        fenced.push(code);
+    }
+    marked.push_str(&content[copied..]);
+    (marked, fenced)
+}
+
+// Swaps marked blocks for their highlighted node, and highlights indented blocks as plain text.
+// Math blocks and raw HTML keep their own `<pre><code>`.
+fn highlight_code_blocks(
+    html: &str,
+    code_blocks: &[&Code],
+    extensions: MarkdownExtensions,
+) -> String {
+    let mut result = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find(CODE_BLOCK_START) {
+        let end = match rest[start..].find(CODE_BLOCK_END) {
+            Some(end) => start + end + CODE_BLOCK_END.len(),
+            None => break,
+        };
+        result.push_str(&rest[..start]);
+        let block = &rest[start..end];
+        let marker = block[CODE_BLOCK_START.len()..]
+            .strip_prefix(" class=\"language-")
+            .and_then(|class| class.strip_prefix(CODE_BLOCK_MARKER))
+            .and_then(|class| class.split_once('"'))
+            .and_then(|(index, _)| index.parse::<usize>().ok())
+            .and_then(|index| code_blocks.get(index));
+        match marker {
+            Some(code) => {
+                let options = CodeBlockOptions::parse(code.meta.as_deref());
+                let highlighted = highlight_code_block(&code.value, code.lang.as_deref(), &options);
+                result.push_str(&highlighted.into_string());
+            }
+            // Raw HTML could contain its own plain `<pre><code>`.
+            None if block.starts_with("<pre><code>") && !extensions.html_enabled() => {
+                let code =
+                    html_to_text(&block["<pre><code>".len()..block.len() - CODE_BLOCK_END.len()]);
+                let highlighted = highlight_code_block(&code, None, &CodeBlockOptions::default());
+                result.push_str(&highlighted.into_string());
+            }
+            None => result.push_str(block),
+        }
+        rest = &rest[end..];
+    }
+    result.push_str(rest);
+    result
+}
+
+// Gives every plain `<hN>` a slugified `id` and a hover anchor link, and collects them.
+// Headings that already have attributes (like the footnotes label) are left alone.
+// Ids already used by the page layout are taken, so a "Content" heading gets `content-1`.
+fn add_heading_anchors(html: &str) -> (String, Vec<Heading>) {
+    let mut result = String::with_capacity(html.len());
+    let mut headings = Vec::new();
+    let mut used_ids: HashSet<String> = LAYOUT_IDS.iter().map(|id| id.to_string()).collect();
+    let mut rest = html;
+    while let Some(start) = rest.find("<h") {
+        let level = rest[start + 2..]
+            .chars()
+            .next()
+            .and_then(|level| level.to_digit(10))
+            .filter(|level| (1..=6).contains(level))
+            .map(|level| level as usize);
+        let (level, close_tag) = match level {
+            Some(level) if rest[start + 3..].starts_with('>') => (level, format!("</h{}>", level)),
+            _ => {
+                result.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+                continue;
+            }
+        };
+        let inner_start = start + 4;
+        let inner_end = match rest[inner_start..].find(&close_tag) {
+            Some(end) => inner_start + end,
+            None => break,
+        };
+        let inner_html = &rest[inner_start..inner_end];
+        let text = html_to_text(inner_html);
+        let id = unique_id(slugify(&text), &mut used_ids);
+        result.push_str(&rest[..start]);
+        result.push_str(
+            &html! {
+                (PreEscaped(format!("<h{} id=\"{}\" class=\"heading\">", level, id)))
+                (PreEscaped(inner_html))
+                a .heading-anchor href={ "#" (id) } aria-label={ "Link to " (text) } { "#" }
+                (PreEscaped(&close_tag))
+            }
+            .into_string(),
+        );
+        headings.push(Heading { level, id, text });
+        rest = &rest[inner_end + close_tag.len()..];
+    }
+    result.push_str(rest);
+    (result, headings)
+}
+
+// Strips tags and decodes the entities `markdown` escapes.
+fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+// "Project Requirements!" => "project-requirements"
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+    for c in text.trim().chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if (c.is_whitespace() || c == '-' || c == '_') && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_matches('-').to_string()
+}
+
+fn unique_id(slug: String, used_ids: &mut HashSet<String>) -> String {
+    let slug = if slug.is_empty() {
+        "section".to_string()
+    } else {
+        slug
+    };
+    let mut id = slug.clone();
+    let mut suffix = 1;
+    while !used_ids.insert(id.clone()) {
+        id = format!("{}-{}", slug, suffix);
+        suffix += 1;
+    }
+    id
+}
+
+// Nested list of links to the headings, indented by heading level.
+pub fn table_of_contents(headings: &[Heading]) -> Markup {
+    let level = headings
+        .iter()
+        .map(|heading| heading.level)
+        .min()
+        .unwrap_or(1);
+    html! {
+        ul .toc-list {
+            @for (i, heading) in headings.iter().enumerate() {
+                @if heading.level <= level {
+                    // Deeper headings up to the next heading at this level are nested under it.
+                    @let children_end = headings[i + 1..]
+                        .iter()
+                        .position(|next| next.level <= level)
+                        .map_or(headings.len(), |end| i + 1 + end);
+                    li {
+                        a href={ "#" (heading.id) } { (heading.text) }
+                        @if children_end > i + 1 {
+                            (table_of_contents(&headings[i + 1..children_end]))
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Footnote definitions are rendered at the end of the page, after code blocks written below
+    // them, so each block has to be matched by its marker and not by order.
+    #[test]
+    fn code_blocks_in_footnotes_keep_their_place() {
+        let content = "Text[^1]\n\n\
+            [^1]: A note.\n\n    ```python\n    python_marker = 1\n    ```\n\n\
+            ```rust linenos\nlet rust_marker = 2;\n```\n\n    indented_marker\n";
+        let extensions = MarkdownExtensions {
+            gfm: Some(true),
+            ..MarkdownExtensions::default()
+        };
+        let html = render_markdown(content, extensions).html.into_string();
+        let (body, footnotes) = html.split_once("data-footnotes").unwrap();
+        assert!(body.contains("rust_marker") && !body.contains("python_marker"));
+        assert!(body.contains(r#"data-lang="rust""#) && body.contains("line-numbers"));
+        assert!(body.contains("indented_marker"));
+        assert!(footnotes.contains("python_marker") && !footnotes.contains("rust_marker"));
+        assert!(footnotes.contains(r#"data-lang="python""#));
+        assert!(!html.contains(CODE_BLOCK_MARKER));
+    }
+
+    #[test]
+    fn heading_ids_skip_layout_ids() {
+        let rendered = render_markdown(
+            "# Content\n\n## Navbar\n\n## Content\n",
+            MarkdownExtensions::default(),
+        );
+        let ids: Vec<&str> = rendered
+            .headings
+            .iter()
+            .map(|heading| heading.id.as_str())
+            .collect();
+        assert_eq!(ids, ["content-1", "navbar-1", "content-2"]);
+    }
+}
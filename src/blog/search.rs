@@ -0,0 +1,342 @@
+// Full-text search over blog posts.
+// `SearchIndex` is an inverted index from lowercase terms to the posts containing them, built
+// in `BlogState::load` next to the `BlogPostIndex`. Terms are kept sorted so a query term also
+// matches every term it is a prefix of ("comp" finds "compiler").
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use axum::{extract::State, http::StatusCode};
+use maud::{html, Markup};
+use serde::{Deserialize, Serialize};
+use serde_qs::axum::OptionalQsQuery;
+
+use crate::{
+    blog::{
+        page::blog_post_dates_markup,
+        render::{render_inline_markdown, render_plain_text},
+        BlogID, BlogPostIndex, BlogPostInfo, PreviewMode,
+    },
+    config::site_config,
+    helpers::{PageMeta, PageType, WebPage},
+    BlogState,
+};
+
+// Prefix matches count for less than exact ones.
+const PREFIX_MATCH_FACTOR: f32 = 0.5;
+const SNIPPET_CHARS: usize = 200;
+// How much of the snippet comes before the first match.
+const SNIPPET_LEAD_CHARS: usize = 60;
+
+#[derive(Clone, Copy)]
+enum SearchField {
+    Title = 0,
+    Tag = 1,
+    Tagline = 2,
+    Body = 3,
+}
+
+const FIELD_WEIGHTS: [f32; 4] = [5.0, 4.0, 3.0, 1.0];
+
+// Occurrences of a term in each `SearchField` of one post.
+#[derive(Default, Clone, Copy)]
+struct FieldCounts([u32; 4]);
+
+impl FieldCounts {
+    // Repeated occurrences are dampened so long posts don't win on volume alone.
+    fn score(&self) -> f32 {
+        self.0
+            .iter()
+            .zip(FIELD_WEIGHTS)
+            .filter(|(count, _)| **count > 0)
+            .map(|(count, weight)| weight * (1.0 + (*count as f32).ln()))
+            .sum()
+    }
+}
+
+#[derive(Default)]
+pub struct SearchIndex {
+    terms: BTreeMap<String, HashMap<BlogID, FieldCounts>>,
+    // Plain text of every post body, for snippets.
+    texts: HashMap<BlogID, String>,
+}
+
+pub struct SearchResult<'a> {
+    pub blog_id: &'a BlogID,
+    pub score: f32,
+}
+
+// Lowercase runs of letters and digits.
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+}
+
+impl SearchIndex {
+    pub fn build(index: &BlogPostIndex) -> Self {
+        let mut search_index = SearchIndex::default();
+        for (blog_id, post_info) in &index.posts {
+            search_index.add(blog_id, SearchField::Title, &post_info.title);
+            search_index.add(blog_id, SearchField::Tagline, &post_info.tagline);
+            for tag in &post_info.tags {
+                search_index.add(blog_id, SearchField::Tag, &tag.to_string());
+                for alias in &tag.info().aliases {
+                    search_index.add(blog_id, SearchField::Tag, alias);
+                }
+            }
+            let text = match post_info.load_markdown() {
+                Ok(md) => render_plain_text(&md, post_info.markdown),
+                Err(err) => {
+                    eprintln!("Error loading {} for search: {}", blog_id, err);
+                    String::new()
+                }
+            };
+            search_index.add(blog_id, SearchField::Body, &text);
+            search_index.texts.insert(blog_id.clone(), text);
+        }
+        search_index
+    }
+
+    fn add(&mut self, blog_id: &BlogID, field: SearchField, text: &str) {
+        for term in tokenize(text) {
+            let counts = self
+                .terms
+                .entry(term)
+                .or_default()
+                .entry(blog_id.clone())
+                .or_default();
+            counts.0[field as usize] += 1;
+        }
+    }
+
+    // Posts containing every query term (or a term starting with it), best match first.
+    pub fn search(&self, query: &str) -> Vec<SearchResult<'_>> {
+        let mut scores: Option<HashMap<&BlogID, f32>> = None;
+        for query_term in tokenize(query) {
+            let mut term_scores: HashMap<&BlogID, f32> = HashMap::new();
+            for (term, postings) in self.terms.range(query_term.clone()..) {
+                if !term.starts_with(&query_term) {
+                    break;
+                }
+                let factor = match *term == query_term {
+                    true => 1.0,
+                    false => PREFIX_MATCH_FACTOR,
+                };
+                for (blog_id, counts) in postings {
+                    *term_scores.entry(blog_id).or_default() += counts.score() * factor;
+                }
+            }
+            scores = Some(match scores {
+                None => term_scores,
+                Some(scores) => scores
+                    .into_iter()
+                    .filter_map(|(blog_id, score)| {
+                        term_scores
+                            .get(blog_id)
+                            .map(|term_score| (blog_id, score + term_score))
+                    })
+                    .collect(),
+            });
+        }
+        let mut results: Vec<SearchResult> = scores
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(blog_id, score)| SearchResult { blog_id, score })
+            .collect();
+        results.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.blog_id.slug().cmp(b.blog_id.slug()))
+        });
+        results
+    }
+
+    // Each post's terms weighted by TF-IDF, so words most posts use count for little. Used to
+    // find related posts, see `blog::related`.
+    pub fn term_weights(&self) -> HashMap<&BlogID, HashMap<&str, f32>> {
+        let post_count = self.texts.len() as f32;
+        let mut weights: HashMap<&BlogID, HashMap<&str, f32>> = HashMap::new();
+        for (term, postings) in &self.terms {
+            let idf = (post_count / postings.len() as f32).ln();
+            if idf <= 0.0 {
+                continue;
+            }
+            for (blog_id, counts) in postings {
+                weights
+                    .entry(blog_id)
+                    .or_default()
+                    .insert(term, counts.score() * idf);
+            }
+        }
+        weights
+    }
+
+    // A window of the post's text around the first match, with matching words in `<mark>`.
+    pub fn snippet(&self, blog_id: &BlogID, query: &str) -> Markup {
+        let query_terms: Vec<String> = tokenize(query).collect();
+        let is_match = |word: &str| {
+            let word = word.to_lowercase();
+            query_terms
+                .iter()
+                .any(|term| word.starts_with(term.as_str()))
+        };
+        let text = self.texts.get(blog_id).map_or("", String::as_str);
+        let words = split_words(text);
+        let first_match = words
+            .iter()
+            .position(|(word, is_word)| *is_word && is_match(word))
+            .unwrap_or(0);
+        // Back up to a word boundary about `SNIPPET_LEAD_CHARS` before the match.
+        let mut start = first_match;
+        let mut lead = 0;
+        while start > 0 && lead < SNIPPET_LEAD_CHARS {
+            start -= 1;
+            lead += words[start].0.chars().count();
+        }
+        let mut length = 0;
+        let end = words[start..]
+            .iter()
+            .position(|(word, _)| {
+                length += word.chars().count();
+                length > SNIPPET_CHARS
+            })
+            .map_or(words.len(), |end| start + end);
+        html! {
+            @if start > 0 { "…" }
+            @for (word, is_word) in &words[start..end] {
+                @if *is_word && is_match(word) {
+                    mark { (word) }
+                } @else {
+                    (word)
+                }
+            }
+            @if end < words.len() { "…" }
+        }
+    }
+}
+
+// Splits text into alternating runs of word and non-word characters, flagging the words.
+fn split_words(text: &str) -> Vec<(&str, bool)> {
+    let mut segments = Vec::new();
+    let mut segment_start = 0;
+    let mut in_word = false;
+    for (i, c) in text.char_indices() {
+        if c.is_alphanumeric() != in_word {
+            if i > segment_start {
+                segments.push((&text[segment_start..i], in_word));
+            }
+            segment_start = i;
+            in_word = c.is_alphanumeric();
+        }
+    }
+    if segment_start < text.len() {
+        segments.push((&text[segment_start..], in_word));
+    }
+    segments
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SearchQuery {
+    #[serde(default)]
+    pub q: String,
+}
+
+fn search_results_markup(
+    results: &[(&BlogID, &BlogPostInfo)],
+    search_index: &SearchIndex,
+    query: &str,
+    preview: PreviewMode,
+) -> Markup {
+    html! {
+        div #search_results {
+            @if query.trim().is_empty() {
+                // Nothing searched yet.
+            } @else if results.is_empty() {
+                article .border .round .middle-align .center-align .medium {
+                    div {
+                        i .extra { "search" }
+                        h2 { "No Posts Found" }
+                        h4 { "Try a different search." }
+                    }
+                }
+            } @else {
+                ul .list .border {
+                    @for (blog_id, post_info) in results {
+                        li {
+                            div .max {
+                                a href=(format!("{}{}", blog_id.url_path(), preview.query_suffix())) {
+                                    h6 .large { (post_info.title) }
+                                    p { (render_inline_markdown(&post_info.tagline)) }
+                                }
+                                p .search-snippet { (search_index.snippet(blog_id, query)) }
+                                p { (blog_post_dates_markup(post_info)) }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Partial for `/search_results?q=`, like `/blog_post_list`.
+pub async fn search_results(
+    OptionalQsQuery(query): OptionalQsQuery<SearchQuery>,
+    preview: PreviewMode,
+    State(state_mutex): State<Arc<Mutex<BlogState>>>,
+) -> Markup {
+    let query = query.unwrap_or_default().q;
+    let mut state = state_mutex.lock().unwrap();
+    state.refresh();
+    let results: Vec<(&BlogID, &BlogPostInfo)> = state
+        .search
+        .search(&query)
+        .into_iter()
+        .filter_map(|result| {
+            state
+                .index
+                .get(result.blog_id, preview)
+                .map(|post_info| (result.blog_id, post_info))
+        })
+        .collect();
+    search_results_markup(&results, &state.search, &query, preview)
+}
+
+pub async fn search_page(
+    OptionalQsQuery(query): OptionalQsQuery<SearchQuery>,
+    preview: PreviewMode,
+    state: State<Arc<Mutex<BlogState>>>,
+) -> WebPage {
+    let q = query
+        .as_ref()
+        .map_or(String::new(), |query| query.q.clone());
+    let title = match q.trim().is_empty() {
+        true => "Search".to_string(),
+        false => format!("Search: {}", q.trim()),
+    };
+    WebPage {
+        title,
+        content: html! {
+            div {
+                h4 .large { "Search" }
+                div .medium-space {}
+                form action="/search" method="get" {
+                    div .field .large .prefix .round .fill {
+                        i { "search" }
+                        input type="search" name="q" value=(q) placeholder="Search posts" {}
+                    }
+                    @if let (true, Some(secret)) = (preview.0, &site_config().preview_secret) {
+                        input type="hidden" name="preview" value=(secret) {}
+                    }
+                }
+                (search_results(OptionalQsQuery(query), preview, state).await)
+            }
+        },
+        page_type: PageType::Blog,
+        status: StatusCode::OK,
+        meta: PageMeta::new("Search Jordi's blog posts.", "/search"),
+    }
+}
@@ -0,0 +1,113 @@
+// Multi-part posts, grouped by the `series` frontmatter field and ordered by `series_part`:
+//
+// series: Building StrawberryLang
+// series_part: 2
+//
+// Each part shows a box listing the whole series, and `/series/{slug}` lists it on its own page.
+// The slug is the name through `render::slugify`, so `/series/building-strawberrylang`.
+
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+};
+use maud::{html, Markup};
+
+use crate::{
+    blog::{
+        page::get_posts_list_markup, render::slugify, BlogID, BlogPostIndex, BlogPostInfo,
+        PreviewMode,
+    },
+    helpers::{PageMeta, PageType, WebPage},
+    BlogState,
+};
+
+// "Part 2 of 3 in Building StrawberryLang", with every part listed and links to the parts
+// either side of this one.
+pub fn series_box_markup(
+    blog_id: &BlogID,
+    post_info: &BlogPostInfo,
+    index: &BlogPostIndex,
+    preview: PreviewMode,
+) -> Markup {
+    let Some(series) = &post_info.series else {
+        return html! {};
+    };
+    let slug = slugify(series);
+    let parts = index.series(&slug, preview);
+    let Some(position) = parts.iter().position(|(id, _)| *id == blog_id) else {
+        return html! {};
+    };
+    let previous = position.checked_sub(1).and_then(|i| parts.get(i));
+    let next = parts.get(position + 1);
+    html! {
+        article .series-box .border .round {
+            h6 {
+                "Part " (position + 1) " of " (parts.len()) " in "
+                a .link href={ "/series/" (slug) (preview.query_suffix()) } { (series) }
+            }
+            ol {
+                @for (part_id, part_info) in &parts {
+                    li {
+                        @if *part_id == blog_id {
+                            strong { (part_info.title) }
+                        } @else {
+                            a .link href={ (part_id.url_path()) (preview.query_suffix()) } { (part_info.title) }
+                        }
+                    }
+                }
+            }
+            nav {
+                @if let Some((previous_id, _)) = previous {
+                    a .button .border .small href={ (previous_id.url_path()) (preview.query_suffix()) } {
+                        i { "arrow_back" }
+                        span { "Previous part" }
+                    }
+                }
+                div .max {}
+                @if let Some((next_id, _)) = next {
+                    a .button .small href={ (next_id.url_path()) (preview.query_suffix()) } {
+                        span { "Next part" }
+                        i { "arrow_forward" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub async fn series_page(
+    Path(slug): Path<String>,
+    preview: PreviewMode,
+    State(state_mutex): State<Arc<Mutex<BlogState>>>,
+) -> WebPage {
+    let mut state = state_mutex.lock().unwrap();
+    state.refresh();
+    let parts = state.index.series(&slug, preview);
+    // Named after the first part's spelling of the series.
+    let Some(name) = parts
+        .first()
+        .and_then(|(_, post_info)| post_info.series.clone())
+    else {
+        return WebPage::not_found("Series not found.");
+    };
+    let part_count = parts.len();
+    WebPage {
+        title: name.clone(),
+        content: html! {
+            h4 .large { (name) }
+            p .large-text {
+                "A series in " (part_count) @if part_count == 1 { " part" } @else { " parts" } "."
+            }
+            div .medium-space {}
+            (get_posts_list_markup(parts, preview, None))
+        },
+        page_type: PageType::Blog,
+        status: StatusCode::OK,
+        meta: PageMeta::new(
+            format!("Every part of Jordi's series {}, in order.", name),
+            format!("/series/{}", slug),
+        ),
+    }
+}
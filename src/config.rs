@@ -0,0 +1,80 @@
+// Site-wide settings.
+// Read once from `site.yml` next to the binary. Every field has a default, so the
+// file is optional. Secrets are also read from the environment so they don't
+// have to be committed.
+
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::blog::render::MarkdownExtensions;
+
+const SITE_CONFIG_PATH: &str = "./site.yml";
+
+static SITE_CONFIG: OnceLock<SiteConfig> = OnceLock::new();
+
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct SiteConfig {
+    // Public origin used for absolute links in feeds, without a trailing slash.
+    pub site_url: String,
+    pub site_title: String,
+    // Passing `?preview=<secret>` shows drafts and scheduled posts.
+    // Preview mode is disabled when this is not set. Overridden by `PREVIEW_SECRET`.
+    pub preview_secret: Option<String>,
+    // Paths listed as `Disallow:` in `/robots.txt`.
+    pub robots_disallow: Vec<String>,
+    // Markdown extensions for every post, see `MarkdownExtensions`.
+    pub markdown: MarkdownExtensions,
+}
+
+impl Default for SiteConfig {
+    fn default() -> Self {
+        Self {
+            site_url: "https://jordigulley.com".into(),
+            site_title: "Jordi's Blog Posts".into(),
+            preview_secret: None,
+            robots_disallow: vec!["/admin/".into()],
+            markdown: MarkdownExtensions::default(),
+        }
+    }
+}
+
+impl SiteConfig {
+    fn load() -> Self {
+        let mut config = match std::fs::read_to_string(SITE_CONFIG_PATH) {
+            Ok(yaml) => match serde_yml::from_str::<SiteConfig>(&yaml) {
+                Ok(config) => config,
+                Err(err) => {
+                    eprintln!(
+                        "Error parsing {}, using defaults: {}",
+                        SITE_CONFIG_PATH, err
+                    );
+                    SiteConfig::default()
+                }
+            },
+            Err(_) => SiteConfig::default(),
+        };
+        if let Ok(secret) = std::env::var("PREVIEW_SECRET") {
+            config.preview_secret = Some(secret);
+        }
+        config.preview_secret = config.preview_secret.filter(|secret| !secret.is_empty());
+        config.site_url = config.site_url.trim_end_matches('/').to_string();
+        config
+    }
+
+    // Absolute URL for a site path such as `/blog/jem_game`.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.site_url, path)
+    }
+
+    pub fn is_preview_secret(&self, secret: &str) -> bool {
+        self.preview_secret
+            .as_deref()
+            .is_some_and(|preview_secret| preview_secret == secret)
+    }
+}
+
+pub fn site_config() -> &'static SiteConfig {
+    SITE_CONFIG.get_or_init(SiteConfig::load)
+}
@@ -2,15 +2,43 @@
 // All pages create a WebPage and add their content through it.
 // WebPage implements IntoResponse so it gets converted into an HTML response
 
-use axum::response::{Html, IntoResponse};
-use maud::{html, Markup, Render, DOCTYPE};
+use axum::{http::StatusCode, response::IntoResponse};
+use chrono::{DateTime, Utc};
+use maud::{html, Markup, PreEscaped, DOCTYPE};
+use serde_json::json;
 
-use crate::blog::{content_tags_html, BlogID, BlogPostInfo};
+use crate::{
+    blog::{
+        content_tags_html,
+        page::{blog_post_dates_markup, reading_time_markup},
+        related::related_posts_markup,
+        render::{inline_plain_text, render_inline_markdown, table_of_contents},
+        series::series_box_markup,
+        BlogID, BlogPostInfo, PreviewMode,
+    },
+    config::site_config,
+    BlogState,
+};
+
+const AUTHOR_NAME: &str = "Jordi Gulley";
+// Used for link previews of pages without their own image.
+const DEFAULT_IMAGE_PATH: &str = "/assets/portrait.jpg";
+// Posts with at least this many headings get a table of contents unless `toc: false` is set.
+const TOC_MIN_HEADINGS: usize = 3;
+// Element ids in the page layout below. Post heading ids must not reuse them.
+pub const LAYOUT_IDS: &[&str] = &[
+    "hey_im",
+    "first_name_animate",
+    "last_name_animate",
+    "navbar",
+    "content",
+];
 
 #[derive(PartialEq)]
 pub enum PageType {
     Home,
     Blog,
+    Projects,
     Error,
 }
 
@@ -18,40 +46,199 @@ pub struct WebPage {
     pub title: String,
     pub content: Markup,
     pub page_type: PageType,
+    pub status: StatusCode,
+    pub meta: PageMeta,
+}
+
+// Metadata for search engines and link previews (OpenGraph, Twitter cards and JSON-LD).
+#[derive(Default)]
+pub struct PageMeta {
+    pub description: Option<String>,
+    // Site path such as `/blog/jem_game`, made absolute with `SiteConfig::site_url`.
+    pub canonical_path: Option<String>,
+    // Site path or absolute URL.
+    pub image: Option<String>,
+    pub article: Option<ArticleMeta>,
+    // schema.org structured data, rendered as an `application/ld+json` script.
+    pub json_ld: Option<serde_json::Value>,
+}
+
+pub struct ArticleMeta {
+    pub published_at: DateTime<Utc>,
+    pub modified_at: Option<DateTime<Utc>>,
+    pub tags: Vec<String>,
+}
+
+impl PageMeta {
+    pub fn new(description: impl Into<String>, canonical_path: impl Into<String>) -> Self {
+        Self {
+            description: Some(description.into()),
+            canonical_path: Some(canonical_path.into()),
+            ..Default::default()
+        }
+    }
+
+    fn image_url(&self) -> String {
+        let image = self.image.as_deref().unwrap_or(DEFAULT_IMAGE_PATH);
+        match image.starts_with('/') {
+            true => site_config().url(image),
+            false => image.to_string(),
+        }
+    }
+
+    fn json_ld_script(&self) -> Option<PreEscaped<String>> {
+        // Escape `</` so the JSON can't close the script tag early.
+        self.json_ld
+            .as_ref()
+            .map(|json_ld| PreEscaped(json_ld.to_string().replace("</", "<\\/")))
+    }
+
+    fn markup(&self, title: &str) -> Markup {
+        let canonical_url = self
+            .canonical_path
+            .as_deref()
+            .map(|path| site_config().url(path));
+        html! {
+            @if let Some(description) = &self.description {
+                meta name="description" content=(description) {}
+                meta property="og:description" content=(description) {}
+                meta name="twitter:description" content=(description) {}
+            }
+            @if let Some(canonical_url) = &canonical_url {
+                link rel="canonical" href=(canonical_url) {}
+                meta property="og:url" content=(canonical_url) {}
+            }
+            meta property="og:title" content=(title) {}
+            meta property="og:site_name" content=(AUTHOR_NAME) {}
+            meta property="og:image" content=(self.image_url()) {}
+            meta name="twitter:title" content=(title) {}
+            meta name="twitter:image" content=(self.image_url()) {}
+            @if let Some(article) = &self.article {
+                meta property="og:type" content="article" {}
+                meta property="article:author" content=(AUTHOR_NAME) {}
+                meta property="article:published_time" content=(article.published_at.to_rfc3339()) {}
+                @if let Some(modified_at) = article.modified_at {
+                    meta property="article:modified_time" content=(modified_at.to_rfc3339()) {}
+                }
+                @for tag in &article.tags {
+                    meta property="article:tag" content=(tag) {}
+                }
+            } @else {
+                meta property="og:type" content="website" {}
+            }
+            @if self.image.is_some() {
+                meta name="twitter:card" content="summary_large_image" {}
+            } @else {
+                meta name="twitter:card" content="summary" {}
+            }
+            @if let Some(json_ld) = self.json_ld_script() {
+                script type="application/ld+json" { (json_ld) }
+            }
+        }
+    }
+}
+
+// schema.org `Person` for the site owner, used on the home page and as the author of posts.
+pub fn person_json_ld() -> serde_json::Value {
+    json!({
+        "@type": "Person",
+        "name": AUTHOR_NAME,
+        "url": site_config().url("/"),
+        "image": site_config().url(DEFAULT_IMAGE_PATH),
+        "jobTitle": "Software Engineer",
+        "sameAs": ["https://github.com/jordigulley"],
+    })
 }
 
 impl WebPage {
-    pub fn error_page<T: std::fmt::Debug>(error: T) -> Self {
+    fn error_page(status: StatusCode, message: &str) -> Self {
         Self {
-            title: "Error".into(),
+            title: status.canonical_reason().unwrap_or("Error").into(),
             content: html! {
-                h1 {
-                    (format!("An error has occured: {:?}",error))
+                article .border .round .middle-align .center-align .medium {
+                    div {
+                        i .extra { "error" }
+                        h2 { (status.as_u16()) }
+                        h4 { (message) }
+                        a href="/" {
+                            button { i { "home" } span { "Back to the home page" } }
+                        }
+                    }
                 }
             },
             page_type: PageType::Error,
+            status,
+            meta: PageMeta::default(),
         }
     }
 
-    pub fn blog_page(blog_id: BlogID, info: &BlogPostInfo) -> Self {
-        let post_html = match blog_id.load_html() {
-            Ok(html) => html,
-            Err(err) => return WebPage::error_page(err),
+    // 404, with `message` saying what wasn't found.
+    pub fn not_found(message: &str) -> Self {
+        WebPage::error_page(StatusCode::NOT_FOUND, message)
+    }
+
+    // 500. `error` is only logged, visitors get a generic message.
+    pub fn internal_error(context: &str, error: impl std::fmt::Display) -> Self {
+        eprintln!("Error {}: {}", context, error);
+        WebPage::error_page(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Something went wrong on our end. Please try again later.",
+        )
+    }
+
+    pub fn blog_page(
+        blog_id: BlogID,
+        info: &BlogPostInfo,
+        state: &BlogState,
+        preview: PreviewMode,
+    ) -> Self {
+        let rendered = match state.render_cache.render(info) {
+            Ok(rendered) => rendered,
+            Err(err) => {
+                return WebPage::internal_error(&format!("rendering post {}", blog_id), err)
+            }
         };
+        let show_toc = info
+            .toc
+            .unwrap_or(rendered.headings.len() >= TOC_MIN_HEADINGS);
         WebPage {
             title: info.title.clone(),
             content: html! {
                 header { h1 class="large" {
                     (info.title)
                 }
-                h4 class="large" { (info.tagline) }
+                h4 class="large" { (render_inline_markdown(&info.tagline)) }
+                p { (blog_post_dates_markup(info)) " · " (reading_time_markup(info)) }
+                @for project_id in &info.projects {
+                    @if let Some(project_info) = state.projects.get(project_id) {
+                        p .post-project {
+                            i { "folder" }
+                            " Part of "
+                            a .link href={ "/projects/" (project_id) } { (project_info.name) }
+                        }
+                    }
+                }
                 div {
                     (content_tags_html(&info.tags))
                 }
             }
-                (post_html)
+                (series_box_markup(&blog_id, info, &state.index, preview))
+                @if show_toc && !rendered.headings.is_empty() {
+                    nav .toc .border .round .padding {
+                        h6 { "Contents" }
+                        (table_of_contents(&rendered.headings))
+                    }
+                }
+                (rendered.html)
+                (related_posts_markup(&blog_id, &state.index, &state.related, preview))
+                @if info.markdown.resolve().math_enabled() {
+                    link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/katex@0.16/dist/katex.min.css" {}
+                    script type="module" src="/assets/math.js" {}
+                }
             },
             page_type: PageType::Blog,
+            status: StatusCode::OK,
+            meta: blog_page_meta(&blog_id, info),
         }
     }
 
@@ -64,9 +251,14 @@ impl WebPage {
                     // meta name="viewport" content="width=device-width, initial-scale=1.0" {}
                     link rel="stylesheet" href="/assets/beercss/beer.min.css" {}
                     link rel="stylesheet" href="/assets/stylesheet.css" {}
+                    link rel="stylesheet" href="/highlight.css" {}
+                    link rel="alternate" type="application/rss+xml" title="RSS" href="/feed.xml" {}
+                    link rel="alternate" type="application/atom+xml" title="Atom" href="/atom.xml" {}
+                    link rel="alternate" type="application/feed+json" title="JSON Feed" href="/feed.json" {}
                     title {
                         (self.title)
                     }
+                    (self.meta.markup(&self.title))
                 }
                 body class="light" {
                     header class="center-align fixed max" {
@@ -85,6 +277,10 @@ impl WebPage {
                                 i {"book"}
                                 "Blog"
                             }
+                            a href="/projects" .active[self.page_type == PageType::Projects] {
+                                i {"folder"}
+                                "Projects"
+                            }
                         }
                         div .right .absolute {
                             a href="https://github.com/jordigulley/" .margin {
@@ -109,8 +305,40 @@ impl WebPage {
 
 impl IntoResponse for WebPage {
     fn into_response(self) -> axum::response::Response {
-        self.get_markup().into_response()
+        let status = self.status;
+        (status, self.get_markup()).into_response()
+    }
+}
+
+fn blog_page_meta(blog_id: &BlogID, info: &BlogPostInfo) -> PageMeta {
+    let canonical_path = blog_id.url_path();
+    let tags: Vec<String> = info.tags.iter().map(|tag| tag.to_string()).collect();
+    // The page renders the tagline as markdown, meta tags get it as plain text.
+    let description = inline_plain_text(&info.tagline);
+    let mut meta = PageMeta::new(description.clone(), canonical_path.clone());
+    meta.image = info.image.clone();
+    let mut json_ld = json!({
+        "@context": "https://schema.org",
+        "@type": "BlogPosting",
+        "headline": info.title,
+        "description": description,
+        "url": site_config().url(&canonical_path),
+        "mainEntityOfPage": site_config().url(&canonical_path),
+        "datePublished": info.published_at.to_rfc3339(),
+        "dateModified": info.updated.unwrap_or(info.published_at).to_rfc3339(),
+        "keywords": tags,
+        "author": person_json_ld(),
+    });
+    if info.image.is_some() {
+        json_ld["image"] = meta.image_url().into();
     }
+    meta.json_ld = Some(json_ld);
+    meta.article = Some(ArticleMeta {
+        published_at: info.published_at,
+        modified_at: info.updated,
+        tags,
+    });
+    meta
 }
 
 pub fn remove_quotes(string: String) -> String {
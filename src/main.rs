@@ -1,31 +1,40 @@
 use std::{
-    collections::HashMap,
-    io,
-    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    net::{Ipv4Addr, SocketAddr},
     sync::{Arc, Mutex},
     time::SystemTime,
 };
 
 use axum::{
-    extract::{Path, State},
-    response::{Html, IntoResponse},
+    extract::{rejection::PathRejection, Path, State},
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
     routing::get,
     Router,
 };
+mod admin;
 mod blog;
+mod config;
 mod helpers;
 mod projects;
+mod sitemap;
+mod tags;
 use axum_server::tls_rustls::RustlsConfig;
-use futures::future::{try_join, try_join_all};
 use helpers::WebPage;
-use maud::{html, Markup, PreEscaped, Render, DOCTYPE};
-use projects::PROJECTS;
+use maud::html;
 use serde_qs::axum::OptionalQsQuery;
 use tower_http::services::ServeDir;
 
 use crate::{
-    blog::{BlogID, BlogPostIndex},
-    helpers::PageType,
+    blog::{
+        cache::RenderCache,
+        diagnostics::{IndexDiagnostic, IndexDiagnosticKind},
+        related::RelatedPosts,
+        search::SearchIndex,
+        BlogID, BlogPostIndex, PreviewMode,
+    },
+    helpers::{PageMeta, PageType},
+    projects::ProjectIndex,
+    tags::TagRegistry,
 };
 
 // Port we will host our HTTPS Server on.
@@ -33,29 +42,95 @@ const HTTPS_PORT: u16 = 443;
 
 struct BlogState {
     index: BlogPostIndex,
-    creation_timestamp: SystemTime, // is_dirty checks creation_timestamp and folder modify timestamp
+    // Problems found in `./posts/` and `tags.yml` during the last load.
+    diagnostics: Vec<IndexDiagnostic>,
+    tags: TagRegistry,
+    projects: ProjectIndex,
+    search: SearchIndex,
+    related: RelatedPosts,
+    // Kept across reloads, see `refresh`.
+    render_cache: RenderCache,
+    robots_txt: String,
+    creation_timestamp: Option<SystemTime>, // is_dirty checks creation_timestamp and folder modify timestamp
+    tags_timestamp: Option<SystemTime>,
+    projects_timestamp: Option<SystemTime>,
 }
 
 impl BlogState {
-    pub fn load() -> Result<Self, io::Error> {
-        Ok(BlogState {
-            index: blog::index_blog_posts()?,
-            creation_timestamp: blog::get_blog_posts_modified_timestamp()?,
-        })
+    // Never fails: an unreadable posts folder results in an empty index and a diagnostic.
+    pub fn load() -> Self {
+        let mut diagnostics = Vec::new();
+        let tags = TagRegistry::load(&mut diagnostics);
+        let projects = match projects::index_projects(&tags) {
+            Ok((projects, project_diagnostics)) => {
+                diagnostics.extend(project_diagnostics);
+                projects
+            }
+            Err(err) => {
+                diagnostics.push(IndexDiagnostic::new(
+                    projects::PROJECT_FOLDER_PATH,
+                    None,
+                    IndexDiagnosticKind::Io(err.to_string()),
+                ));
+                ProjectIndex::new()
+            }
+        };
+        // Posts are checked against the projects they link to, so those come first.
+        let index = match blog::index_blog_posts(&tags, &projects) {
+            Ok((index, post_diagnostics)) => {
+                diagnostics.extend(post_diagnostics);
+                index
+            }
+            Err(err) => {
+                diagnostics.push(IndexDiagnostic::new(
+                    blog::BLOG_POST_FOLDER_PATH,
+                    None,
+                    IndexDiagnosticKind::Io(err.to_string()),
+                ));
+                BlogPostIndex::new()
+            }
+        };
+        for diagnostic in &diagnostics {
+            eprintln!("{}", diagnostic);
+        }
+        let search = SearchIndex::build(&index);
+        BlogState {
+            related: RelatedPosts::build(&index, &search),
+            search,
+            render_cache: RenderCache::default(),
+            robots_txt: sitemap::build_robots_txt(),
+            index,
+            diagnostics,
+            tags,
+            projects,
+            creation_timestamp: blog::get_blog_posts_modified_timestamp().ok(),
+            tags_timestamp: tags::get_tags_modified_timestamp(),
+            projects_timestamp: projects::get_projects_modified_timestamp(),
+        }
     }
 
     pub fn is_dirty(&self) -> bool {
-        self.creation_timestamp
-            != blog::get_blog_posts_modified_timestamp()
-                .expect("Error checking blog posts folder dirty.")
+        self.creation_timestamp != blog::get_blog_posts_modified_timestamp().ok()
+            || self.tags_timestamp != tags::get_tags_modified_timestamp()
+            || self.projects_timestamp != projects::get_projects_modified_timestamp()
+    }
+
+    // Reloads everything when `./posts/`, `./projects/` or `tags.yml` has changed since the last
+    // load.
+    pub fn refresh(&mut self) {
+        if self.is_dirty() {
+            // Unchanged posts keep their rendered HTML.
+            let render_cache = std::mem::take(&mut self.render_cache);
+            *self = BlogState::load();
+            render_cache.retain_indexed(&self.index);
+            self.render_cache = render_cache;
+        }
     }
 }
 
 #[tokio::main]
 async fn main() {
-    let blog_state = Arc::new(Mutex::new(
-        BlogState::load().expect("Error loading blog state."),
-    ));
+    let blog_state = Arc::new(Mutex::new(BlogState::load()));
     let config = RustlsConfig::from_pem_file("certs/cert.pem", "certs/key.pem")
         .await
         .unwrap();
@@ -76,7 +151,23 @@ async fn start_https_server(
         .route("/blog", get(blog::page::blog_post_list_page))
         .route("/blog_post_list", get(blog::page::blog_post_list_items))
         .route("/blog/{blog_id}", get(blog_post))
+        .route("/series/{series}", get(blog::series::series_page))
+        .route("/projects", get(projects::page::project_list_page))
+        .route("/project_list", get(projects::page::project_list_items))
+        .route("/projects/{project_id}", get(projects::page::project_page))
+        .route("/tags", get(tags::page::tags_page))
+        .route("/tags/{tag}", get(tags::page::tag_page))
+        .route("/search", get(blog::search::search_page))
+        .route("/search_results", get(blog::search::search_results))
+        .route("/feed.xml", get(blog::feed::rss_feed))
+        .route("/atom.xml", get(blog::feed::atom_feed))
+        .route("/feed.json", get(blog::feed::json_feed))
+        .route("/highlight.css", get(blog::highlight::highlight_css))
+        .route("/sitemap.xml", get(sitemap::sitemap_xml))
+        .route("/robots.txt", get(sitemap::robots_txt))
+        .route("/admin/status", get(admin::status_page))
         .nest_service("/assets", ServeDir::new("assets"))
+        .fallback(not_found)
         .with_state(blog_state);
     println!("Running on https://{:?}", addr);
     axum_server::bind_rustls(addr, config)
@@ -85,7 +176,12 @@ async fn start_https_server(
         .unwrap();
 }
 
-async fn home(state: State<Arc<Mutex<BlogState>>>) -> Html<WebPage> {
+async fn home(preview: PreviewMode, state: State<Arc<Mutex<BlogState>>>) -> Html<WebPage> {
+    let project_cards = {
+        let mut state = state.lock().unwrap();
+        state.refresh();
+        projects::project_cards(&state.projects.as_sorted_vec(), &state.index, preview)
+    };
     let html = WebPage {
         title: "Jordi's Portfolio".into(),
         content: html! {
@@ -100,13 +196,15 @@ async fn home(state: State<Arc<Mutex<BlogState>>>) -> Html<WebPage> {
                 div {
                 h3 .large { "My Projects" }
                 div .space {	}
-                (get_project_cards())
+                (project_cards)
                 }
                 div .large-space {}
                 div .blog-post-home-page-section {
                 h3 .large { "Blog Posts" }
                 div .space {	}
-                (blog::page::blog_post_list_items(OptionalQsQuery(None), state).await)
+                div data-list-url="/blog_post_list" {
+                    (blog::page::blog_post_list_items(OptionalQsQuery(None), preview, state).await)
+                }
                 }
             }
             h1 .secondary-text { "Contact Me" }
@@ -119,50 +217,53 @@ async fn home(state: State<Arc<Mutex<BlogState>>>) -> Html<WebPage> {
             }
         },
         page_type: PageType::Home,
+        status: StatusCode::OK,
+        meta: PageMeta {
+            json_ld: Some({
+                let mut person = helpers::person_json_ld();
+                person["@context"] = "https://schema.org".into();
+                person
+            }),
+            ..PageMeta::new(
+                "Full-stack software engineer working with React, C++ and Rust. Projects, blog posts and contact information.",
+                "/",
+            )
+        },
     };
     Html(html)
 }
 
+// Aliases and old file names are answered with a permanent redirect to the post's slug.
 async fn blog_post(
-    Path(blog_id): Path<BlogID>,
+    blog_id: Result<Path<BlogID>, PathRejection>,
+    preview: PreviewMode,
     State(state_mutex): State<Arc<Mutex<BlogState>>>,
-) -> WebPage {
+) -> Response {
+    // IDs `BlogID::new` refuses can't name a post.
+    let Ok(Path(blog_id)) = blog_id else {
+        return WebPage::not_found("Blog post not found.").into_response();
+    };
     let mut state = state_mutex.lock().unwrap();
-    if state.is_dirty() {
-        *state = match BlogState::load() {
-            Ok(blog_state) => blog_state,
-            Err(err) => return WebPage::error_page(err),
-        }
-    }
-    let info = match state.index.0.get(&blog_id) {
+    state.refresh();
+    let info = match state.index.get(&blog_id, preview) {
         Some(info) => info,
-        None => return WebPage::error_page("Blog post not found."),
+        None => {
+            return match state.index.redirect(blog_id.slug(), preview) {
+                Some(canonical_id) => (
+                    StatusCode::MOVED_PERMANENTLY,
+                    [(
+                        header::LOCATION,
+                        format!("{}{}", canonical_id.url_path(), preview.query_suffix()),
+                    )],
+                )
+                    .into_response(),
+                None => WebPage::not_found("Blog post not found.").into_response(),
+            }
+        }
     };
-    WebPage::blog_page(blog_id, info)
+    WebPage::blog_page(blog_id, info, &state, preview).into_response()
 }
 
-fn get_project_cards() -> Markup {
-    html! {
-        div class="" {
-            @for project_info in PROJECTS {
-                article class="border" {
-                    h4 {
-                        (project_info.project)
-                    }
-                    p {
-                        (project_info.short_description)
-                    }
-                    div {
-                        (blog::content_tags_html(&project_info.get_tags()))
-                    }
-                    @if !project_info.learn_more_link.is_empty() {
-                        div class="right-align" {a href=(project_info.learn_more_link) {
-                            button { "Learn More" }
-                        }
-                    }
-                }
-                }
-            }
-        }
-    }
+async fn not_found() -> WebPage {
+    WebPage::not_found("Page not found.")
 }
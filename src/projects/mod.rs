@@ -1,139 +1,278 @@
-use std::{fmt::Display, str::FromStr};
-
-use serde::{Deserialize, Serialize};
-
-// Update `ALL_CONTENT_TAGS` when this enum changes.
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
-pub enum ContentTag {
-    Cpp,
-    Rust,
-    GodotEngine,
-    Game,
-    LanguageDevelopment,
-    Compiler,
-    BytecodeVm,
-    Website,
-    WebDevelopment,
-    OpenSource,
+// Projects, loaded from markdown files in `./projects/` like posts are from `./posts/`.
+// The file name is the project's slug, so `projects/fmod_gd4.md` is shown at `/projects/fmod_gd4`.
+// Frontmatter describes the project's card, the markdown body becomes its page.
+
+pub mod page;
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs::DirEntry,
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Utc};
+use maud::{html, Markup};
+use serde::Deserialize;
+
+use crate::{
+    blog::{
+        self,
+        diagnostics::{IndexDiagnostic, IndexDiagnosticKind},
+        frontmatter::{deserialize_frontmatter_date, parse_frontmatter, Frontmatter},
+        render::{self, MarkdownExtensions, RenderedMarkdown},
+        resolve_post_path, BlogPostIndex, PreviewMode,
+    },
+    tags::{ContentTag, TagRegistry},
+};
+
+pub const PROJECT_FOLDER_PATH: &str = "./projects/";
+
+#[derive(Default, Clone, PartialEq, Eq, Hash, Debug, Deserialize)]
+#[serde(transparent)]
+pub struct ProjectID {
+    pub slug: String,
 }
-pub const ALL_CONTENT_TAGS: [ContentTag; 10] = [
-    ContentTag::Cpp,
-    ContentTag::Rust,
-    ContentTag::GodotEngine,
-    ContentTag::Game,
-    ContentTag::LanguageDevelopment,
-    ContentTag::Compiler,
-    ContentTag::BytecodeVm,
-    ContentTag::Website,
-    ContentTag::WebDevelopment,
-    ContentTag::OpenSource,
-];
-
-impl Display for ContentTag {
+
+impl Display for ProjectID {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(match self {
-            ContentTag::Cpp => "C++",
-            ContentTag::Rust => "Rust",
-            ContentTag::GodotEngine => "Godot Engine",
-            ContentTag::Game => "Game",
-            ContentTag::Website => "Website",
-            ContentTag::OpenSource => "Open Source Project",
-            ContentTag::LanguageDevelopment => "Programming Language",
-            ContentTag::Compiler => "Compiler",
-            ContentTag::BytecodeVm => "Bytecode Virtual Machine",
-            ContentTag::WebDevelopment => "Web Development",
-        })
+        f.write_str(&self.slug)
     }
 }
 
-#[derive(Copy, Clone)]
-pub enum Project {
-    Jem = 0,
-    Strawberry = 1,
-    FmodGd4 = 2,
-    LeptosMaterial = 3,
-    Ticks = 4,
+// Ordered from most to least active, for `ProjectSort::Status`.
+#[derive(Deserialize, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectStatus {
+    // Being worked on.
+    Active,
+    // Done, but still receiving fixes.
+    Maintained,
+    Complete,
+    Paused,
+    Archived,
 }
 
-// impl ToString for Project {
-//     fn to_string(&self) -> String {
-//         .to_string()
-//     }
-// }
-
-impl Display for Project {
+impl Display for ProjectStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str(match self {
-            Project::Jem => "JEM",
-            Project::Strawberry => "StrawberryLang",
-            Project::FmodGd4 => "FMOD Integration for Godot 4",
-            Project::LeptosMaterial => "Leptos Material Web Components",
-            Project::Ticks => "Ticks",
+            ProjectStatus::Active => "Active",
+            ProjectStatus::Maintained => "Maintained",
+            ProjectStatus::Complete => "Complete",
+            ProjectStatus::Paused => "Paused",
+            ProjectStatus::Archived => "Archived",
         })
     }
 }
 
-pub const PROJECTS:[ProjectInfo; 5] = [
-    ProjectInfo {
-        project: Project::Jem,
-        short_description: "A fast-paced RPG made with a custom version of the Godot Engine, written in C++.",
-        learn_more_link: "",
-    },
-    ProjectInfo {
-        project: Project::Strawberry,
-        short_description: "A game programming language inspired by Rust's simple syntax and functional programming influences. Made for Godot.",
-        learn_more_link: "",
-    },
-    ProjectInfo {
-        project: Project::FmodGd4,
-        short_description: "C++ integration of the FMOD Sound System for the Godot Engine.",
-        learn_more_link: "https://github.com/jordigulley/fmod_gd4",
-    },
-    ProjectInfo {
-        project: Project::LeptosMaterial,
-        short_description: "A Leptos component wrapper for Material Web Components (MWC), along with some extra components to fill in the gaps.",
-        learn_more_link: "https://github.com/jordigulley/leptos-material",
-    },
-    ProjectInfo {
-        project: Project::Ticks,
-        short_description: "Simple, ergonomic Rust wrapper for the TickTick Open API",
-        learn_more_link: "https://github.com/jordigulley/ticks",
-    },
-];
+#[derive(Deserialize, Debug)]
+pub struct ProjectLink {
+    pub label: String,
+    pub url: String,
+}
 
-#[derive(Clone)]
+#[derive(Deserialize, Debug)]
 pub struct ProjectInfo {
-    pub project: Project,
-    pub short_description: &'static str,
-    pub learn_more_link: &'static str,
+    pub name: String,
+    // One or two sentences for the project's card.
+    pub description: String,
+    // Parsed separately by `parse_frontmatter`, like post tags.
+    #[serde(skip)]
+    pub tags: Vec<ContentTag>,
+    // Source code URL.
+    #[serde(default)]
+    pub repo: Option<String>,
+    // Other links such as downloads or docs, shown next to the repo.
+    #[serde(default)]
+    pub links: Vec<ProjectLink>,
+    // Only shown when set.
+    #[serde(default)]
+    pub status: Option<ProjectStatus>,
+    // Position on the home page and in `/projects`, lowest first. Projects without one come last.
+    #[serde(default)]
+    pub order: Option<u32>,
+    #[serde(default, deserialize_with = "deserialize_frontmatter_date")]
+    pub started: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_frontmatter_date")]
+    pub updated: Option<DateTime<Utc>>,
+    // Cover image, a site path or absolute URL.
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub markdown: MarkdownExtensions,
+    // The file's canonical path, checked to be inside `./projects/` when indexing, like
+    // `BlogPostInfo::path`. The only path the project is ever read from.
+    #[serde(skip)]
+    pub path: PathBuf,
 }
 
 impl ProjectInfo {
-    pub fn get_tags(&self) -> Vec<ContentTag> {
-        match self.project {
-            Project::Jem => vec![ContentTag::Cpp, ContentTag::GodotEngine, ContentTag::Game],
-            Project::Strawberry => vec![
-                ContentTag::Cpp,
-                ContentTag::GodotEngine,
-                ContentTag::LanguageDevelopment,
-                ContentTag::BytecodeVm,
-                ContentTag::Compiler,
-            ],
-            Project::FmodGd4 => vec![
-                ContentTag::Cpp,
-                ContentTag::GodotEngine,
-                ContentTag::OpenSource,
-            ],
-            Project::LeptosMaterial => vec![
-                ContentTag::Rust,
-                ContentTag::WebDevelopment,
-                ContentTag::OpenSource,
-            ],
-            Project::Ticks => vec![
-                ContentTag::Rust,
-                ContentTag::WebDevelopment,
-                ContentTag::OpenSource,
-            ],
+    // The project's markdown file, frontmatter included.
+    pub fn load_markdown(&self) -> Result<String, io::Error> {
+        std::fs::read_to_string(&self.path)
+    }
+
+    pub fn load_html(&self) -> Result<RenderedMarkdown, io::Error> {
+        let md = self.load_markdown()?;
+        Ok(render::render_markdown(&md, self.markdown))
+    }
+
+    // When the project was last worked on, as far as its frontmatter says.
+    pub fn last_active(&self) -> Option<DateTime<Utc>> {
+        self.updated.or(self.started)
+    }
+}
+
+#[derive(Default)]
+pub struct ProjectIndex(pub HashMap<ProjectID, ProjectInfo>);
+
+impl ProjectIndex {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn get(&self, project_id: &ProjectID) -> Option<&ProjectInfo> {
+        self.0.get(project_id)
+    }
+
+    // By `order`, then most recently active first, projects without dates last, then by name.
+    pub fn as_sorted_vec(&self) -> Vec<(&ProjectID, &ProjectInfo)> {
+        let mut vec = self.0.iter().collect::<Vec<(&ProjectID, &ProjectInfo)>>();
+        vec.sort_by(|(_, a), (_, b)| {
+            (a.order.is_none(), a.order)
+                .cmp(&(b.order.is_none(), b.order))
+                .then_with(|| b.last_active().cmp(&a.last_active()))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        vec
+    }
+
+    pub fn with_tag(&self, tag: &ContentTag) -> Vec<(&ProjectID, &ProjectInfo)> {
+        self.as_sorted_vec()
+            .into_iter()
+            .filter(|(_, project_info)| project_info.tags.contains(tag))
+            .collect()
+    }
+}
+
+// Indexes every markdown file in `./projects/`, reporting broken ones like `index_blog_posts`.
+pub fn index_projects(
+    tags: &TagRegistry,
+) -> Result<(ProjectIndex, Vec<IndexDiagnostic>), io::Error> {
+    let folder = std::fs::canonicalize(PROJECT_FOLDER_PATH)?;
+    let dir = std::fs::read_dir(&folder)?;
+    let mut index = ProjectIndex::new();
+    let mut diagnostics = Vec::new();
+    for result in dir {
+        let entry = result?;
+        let path = entry.path();
+        if path.extension().is_none_or(|extension| extension != "md") {
+            continue;
+        }
+        if let Some((project_id, project_info)) =
+            index_project(&entry, &folder, tags, &mut diagnostics)
+        {
+            index.0.insert(project_id, project_info);
+        }
+    }
+    Ok((index, diagnostics))
+}
+
+fn index_project(
+    entry: &DirEntry,
+    folder: &Path,
+    tags: &TagRegistry,
+    diagnostics: &mut Vec<IndexDiagnostic>,
+) -> Option<(ProjectID, ProjectInfo)> {
+    let file_name = entry.file_name().to_string_lossy().to_string();
+    let mut report = |line: Option<usize>, kind: IndexDiagnosticKind| {
+        diagnostics.push(IndexDiagnostic::new(
+            format!("{}{}", PROJECT_FOLDER_PATH, file_name),
+            line,
+            kind,
+        ));
+    };
+    let path = match resolve_post_path(folder, &entry.path()) {
+        Ok(path) => path,
+        Err(err) => {
+            report(None, IndexDiagnosticKind::Io(err.to_string()));
+            return None;
+        }
+    };
+    let project_md = match std::fs::read_to_string(&path) {
+        Ok(project_md) => project_md,
+        Err(err) => {
+            report(None, IndexDiagnosticKind::Io(err.to_string()));
+            return None;
+        }
+    };
+    let Frontmatter {
+        info: mut project_info,
+        tags: project_tags,
+        ..
+    } = parse_frontmatter::<ProjectInfo>(&project_md, tags, &mut report)?;
+    project_info.tags = project_tags;
+    project_info.path = path;
+    let project_id = ProjectID {
+        slug: String::from(Path::new(&file_name).with_extension("").to_string_lossy()),
+    };
+    Some((project_id, project_info))
+}
+
+pub fn get_projects_modified_timestamp() -> Option<SystemTime> {
+    std::fs::metadata(Path::new(PROJECT_FOLDER_PATH))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+// Cards for `projects`, each listing the posts about it.
+pub fn project_cards(
+    projects: &[(&ProjectID, &ProjectInfo)],
+    posts: &BlogPostIndex,
+    preview: PreviewMode,
+) -> Markup {
+    html! {
+        div class="" {
+            @for (project_id, project_info) in projects {
+                @let related_posts = posts.about_project(project_id, preview);
+                article class="border" {
+                    a href={ "/projects/" (project_id) } {
+                        h4 {
+                            (project_info.name)
+                        }
+                    }
+                    p {
+                        (project_info.description)
+                    }
+                    div {
+                        (blog::content_tags_html(&project_info.tags))
+                    }
+                    @if !related_posts.is_empty() {
+                        h6 .small { "Posts" }
+                        ul .related-posts {
+                            @for (blog_id, post_info) in &related_posts {
+                                li {
+                                    a .link href={ (blog_id.url_path()) (preview.query_suffix()) } {
+                                        (post_info.title)
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    div class="right-align" {
+                        @if let Some(repo) = &project_info.repo {
+                            a href=(repo) {
+                                button .border { "Source" }
+                            }
+                        }
+                        a href={ "/projects/" (project_id) } {
+                            button { "Learn More" }
+                        }
+                    }
+                }
+            }
         }
     }
 }
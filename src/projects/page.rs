@@ -0,0 +1,260 @@
+// `/projects`, every project filtered by tags like `/blog`, and `/projects/{slug}`, a project's
+// page rendered from the body of its markdown file.
+
+use std::{
+    fmt::Display,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+};
+use maud::{html, Markup};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use serde_qs::axum::OptionalQsQuery;
+
+use crate::{
+    blog::{
+        content_tags_html,
+        page::{
+            get_blog_post_date_str, get_posts_list_markup, tag_filter_chips, tag_result_counts,
+            TagFilterSet,
+        },
+        PreviewMode,
+    },
+    config::site_config,
+    helpers::{person_json_ld, remove_quotes, PageMeta, PageType, WebPage},
+    projects::{self, ProjectID, ProjectInfo},
+    tags::ContentTag,
+    BlogState,
+};
+
+// Update `ALL_PROJECT_SORTS` when this enum changes.
+#[derive(Serialize, Deserialize, Default, Copy, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectSort {
+    // Most recently active first, see `ProjectInfo::last_active`. Projects without dates keep
+    // their `order`.
+    #[default]
+    Recent,
+    Name,
+    // Active projects first, projects without a status last.
+    Status,
+}
+pub const ALL_PROJECT_SORTS: [ProjectSort; 3] =
+    [ProjectSort::Recent, ProjectSort::Name, ProjectSort::Status];
+
+impl Display for ProjectSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ProjectSort::Recent => "Recently active",
+            ProjectSort::Name => "Name",
+            ProjectSort::Status => "Status",
+        })
+    }
+}
+
+impl ProjectSort {
+    // Expects `projects` as returned by `ProjectIndex::as_sorted_vec`.
+    pub fn sort(&self, projects: &mut [(&ProjectID, &ProjectInfo)]) {
+        match self {
+            ProjectSort::Recent => projects
+                .sort_by_key(|(_, project_info)| std::cmp::Reverse(project_info.last_active())),
+            ProjectSort::Name => {
+                projects.sort_by_key(|(_, project_info)| project_info.name.to_lowercase())
+            }
+            ProjectSort::Status => projects.sort_by_key(|(_, project_info)| {
+                (project_info.status.is_none(), project_info.status)
+            }),
+        }
+    }
+}
+
+// "Started on Mon July 14, 2025 · Updated on …"
+pub fn project_dates_markup(project_info: &ProjectInfo) -> Markup {
+    html! {
+        @if let Some(started) = project_info.started {
+            "Started on " (get_blog_post_date_str(started))
+        }
+        @if let (Some(_), Some(_)) = (project_info.started, project_info.updated) {
+            " · "
+        }
+        @if let Some(updated) = project_info.updated {
+            "Updated on " (get_blog_post_date_str(updated))
+        }
+    }
+}
+
+fn project_page_meta(project_id: &ProjectID, project_info: &ProjectInfo) -> PageMeta {
+    let canonical_path = format!("/projects/{}", project_id);
+    let mut json_ld = json!({
+        "@context": "https://schema.org",
+        "@type": "CreativeWork",
+        "name": project_info.name,
+        "description": project_info.description,
+        "url": site_config().url(&canonical_path),
+        "keywords": project_info.tags.iter().map(|tag| tag.to_string()).collect::<Vec<String>>(),
+        "author": person_json_ld(),
+    });
+    if let Some(repo) = &project_info.repo {
+        json_ld["codeRepository"] = repo.clone().into();
+    }
+    PageMeta {
+        image: project_info.image.clone(),
+        json_ld: Some(json_ld),
+        ..PageMeta::new(project_info.description.clone(), canonical_path)
+    }
+}
+
+pub async fn project_page(
+    Path(project_id): Path<ProjectID>,
+    preview: PreviewMode,
+    State(state_mutex): State<Arc<Mutex<BlogState>>>,
+) -> WebPage {
+    let mut state = state_mutex.lock().unwrap();
+    state.refresh();
+    let project_info = match state.projects.get(&project_id) {
+        Some(project_info) => project_info,
+        None => return WebPage::not_found("Project not found."),
+    };
+    let related_posts = state.index.about_project(&project_id, preview);
+    let rendered = match project_info.load_html() {
+        Ok(rendered) => rendered,
+        Err(err) => {
+            return WebPage::internal_error(&format!("rendering project {}", project_id), err)
+        }
+    };
+    WebPage {
+        title: project_info.name.clone(),
+        content: html! {
+            header {
+                @if let Some(image) = &project_info.image {
+                    img .responsive .round .project-cover src=(image) alt="" {}
+                }
+                h1 .large { (project_info.name) }
+                h4 .large { (project_info.description) }
+                p {
+                    @if let Some(status) = project_info.status {
+                        span .chip { (status) }
+                        " "
+                    }
+                    (project_dates_markup(project_info))
+                }
+                div {
+                    (content_tags_html(&project_info.tags))
+                }
+                nav .wrap {
+                    @if let Some(repo) = &project_info.repo {
+                        a href=(repo) {
+                            button { i { "code" } span { "Source" } }
+                        }
+                    }
+                    @for link in &project_info.links {
+                        a href=(link.url) {
+                            button .border { i { "open_in_new" } span { (link.label) } }
+                        }
+                    }
+                }
+            }
+            (rendered.html)
+            @if !related_posts.is_empty() {
+                div .large-space {}
+                h5 { "Posts about " (project_info.name) }
+                div .space {}
+                (get_posts_list_markup(related_posts, preview, None))
+            }
+            @if project_info.markdown.resolve().math_enabled() {
+                link rel="stylesheet" href="https://cdn.jsdelivr.net/npm/katex@0.16/dist/katex.min.css" {}
+                script type="module" src="/assets/math.js" {}
+            }
+        },
+        page_type: PageType::Projects,
+        status: StatusCode::OK,
+        meta: project_page_meta(&project_id, project_info),
+    }
+}
+
+pub async fn project_list_page(
+    OptionalQsQuery(query): OptionalQsQuery<TagFilterSet<ProjectSort>>,
+    preview: PreviewMode,
+    state: State<Arc<Mutex<BlogState>>>,
+) -> WebPage {
+    WebPage {
+        title: "Jordi's Projects".into(),
+        content: html! {
+            div {
+                h4 .large { "Projects" }
+                div .medium-space {}
+                div data-list-url="/project_list" {
+                    (project_list_items(OptionalQsQuery(query), preview, state).await)
+                }
+            }
+        },
+        page_type: PageType::Projects,
+        status: StatusCode::OK,
+        meta: PageMeta::new(
+            "Games, programming languages and web projects, with the posts written about them.",
+            "/projects",
+        ),
+    }
+}
+
+pub async fn project_list_items(
+    OptionalQsQuery(current_filter_set): OptionalQsQuery<TagFilterSet<ProjectSort>>,
+    preview: PreviewMode,
+    State(state_mutex): State<Arc<Mutex<BlogState>>>,
+) -> Markup {
+    let mut state = state_mutex.lock().unwrap();
+    state.refresh();
+    let state = &*state;
+    let current_filter_set = current_filter_set.map(|filter_set| filter_set.resolved(&state.tags));
+    let filter_set = current_filter_set.as_ref();
+    let mut projects = state.projects.as_sorted_vec();
+    let project_tags: Vec<&[ContentTag]> = projects
+        .iter()
+        .map(|(_, project_info)| project_info.tags.as_slice())
+        .collect();
+    let tag_counts = tag_result_counts(
+        &project_tags,
+        filter_set.unwrap_or(&TagFilterSet::default()),
+        &state.tags,
+    );
+    projects.retain(|(_, project_info)| {
+        filter_set.is_none_or(|filter_set| filter_set.matches_tags(&project_info.tags))
+    });
+    let sort = filter_set
+        .and_then(|filter_set| filter_set.sort)
+        .unwrap_or_default();
+    sort.sort(&mut projects);
+    html! {
+        div #project_list {
+            article .border {
+                h6 { "Filters" }
+                div .space {}
+                (tag_filter_chips(&current_filter_set, &tag_counts, &state.tags))
+                div .space {}
+                h6 { "Sort" }
+                div .space {}
+                @for project_sort in &ALL_PROJECT_SORTS {
+                    @let sort_string = remove_quotes(serde_json::to_string(project_sort).unwrap());
+                    button .chip .primary-container[*project_sort == sort] #sort_posts data-sort=(sort_string) {
+                        (project_sort)
+                    }
+                }
+            }
+            @if projects.is_empty() {
+                article .border .round .middle-align .center-align .medium {
+                    div {
+                        i .extra { "search" }
+                        h2 { "No Projects Found" }
+                        h4 { "Try removing some filters." }
+                    }
+                }
+            } @else {
+                (projects::project_cards(&projects, &state.index, preview))
+            }
+        }
+    }
+}
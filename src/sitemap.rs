@@ -0,0 +1,137 @@
+// `/sitemap.xml` and `/robots.txt`.
+// The sitemap is built on every request, so scheduled posts appear as soon as they're published.
+// `robots.txt` only depends on the site config and is generated in `BlogState::load`.
+
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use maud::{html, PreEscaped};
+
+use crate::{
+    blog::{self, BlogPostIndex, PreviewMode},
+    config::site_config,
+    projects::ProjectIndex,
+    tags::{self, TagRegistry},
+    BlogState,
+};
+
+struct SitemapUrl {
+    path: String,
+    last_modified: Option<DateTime<Utc>>,
+}
+
+pub fn build_sitemap(index: &BlogPostIndex, projects: &ProjectIndex, tags: &TagRegistry) -> String {
+    let posts = index.as_sorted_vec(PreviewMode(false));
+    let blog_last_modified = posts
+        .iter()
+        .map(|(_, post_info)| post_info.updated.unwrap_or(post_info.published_at))
+        .max();
+    let mut urls = vec![
+        SitemapUrl {
+            path: "/".into(),
+            last_modified: blog_last_modified,
+        },
+        SitemapUrl {
+            path: "/blog".into(),
+            last_modified: blog_last_modified,
+        },
+    ];
+    urls.push(SitemapUrl {
+        path: "/tags".into(),
+        last_modified: blog_last_modified,
+    });
+    urls.push(SitemapUrl {
+        path: "/projects".into(),
+        last_modified: projects
+            .as_sorted_vec()
+            .first()
+            .and_then(|(_, project_info)| project_info.last_active()),
+    });
+    for (project_id, project_info) in projects.as_sorted_vec() {
+        urls.push(SitemapUrl {
+            path: format!("/projects/{}", project_id),
+            last_modified: project_info.last_active(),
+        });
+    }
+    for tag in tags::page::used_tags(index, projects, tags) {
+        urls.push(SitemapUrl {
+            path: format!("/tags/{}", tag.slug()),
+            last_modified: posts
+                .iter()
+                .filter(|(_, post_info)| post_info.tags.contains(tag))
+                .map(|(_, post_info)| post_info.updated.unwrap_or(post_info.published_at))
+                .max(),
+        });
+    }
+    for series in index.all_series(PreviewMode(false)) {
+        let slug = blog::render::slugify(&series);
+        urls.push(SitemapUrl {
+            last_modified: index
+                .series(&slug, PreviewMode(false))
+                .iter()
+                .map(|(_, post_info)| post_info.updated.unwrap_or(post_info.published_at))
+                .max(),
+            path: format!("/series/{}", slug),
+        });
+    }
+    for (blog_id, post_info) in posts {
+        urls.push(SitemapUrl {
+            path: blog_id.url_path(),
+            last_modified: Some(post_info.updated.unwrap_or(post_info.published_at)),
+        });
+    }
+    html! {
+        (PreEscaped(r#"<?xml version="1.0" encoding="UTF-8"?>"#))
+        urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9" {
+            @for url in &urls {
+                url {
+                    loc { (site_config().url(&url.path)) }
+                    @if let Some(last_modified) = url.last_modified {
+                        lastmod { (last_modified.format("%Y-%m-%d")) }
+                    }
+                }
+            }
+        }
+    }
+    .into_string()
+}
+
+pub fn build_robots_txt() -> String {
+    let mut robots_txt = String::from("User-agent: *\n");
+    for path in &site_config().robots_disallow {
+        robots_txt.push_str(&format!("Disallow: {}\n", path));
+    }
+    if site_config().robots_disallow.is_empty() {
+        robots_txt.push_str("Allow: /\n");
+    }
+    robots_txt.push_str(&format!(
+        "\nSitemap: {}\n",
+        site_config().url("/sitemap.xml")
+    ));
+    robots_txt
+}
+
+pub async fn sitemap_xml(State(state_mutex): State<Arc<Mutex<BlogState>>>) -> Response {
+    let mut state = state_mutex.lock().unwrap();
+    state.refresh();
+    (
+        [(header::CONTENT_TYPE, "application/xml; charset=utf-8")],
+        build_sitemap(&state.index, &state.projects, &state.tags),
+    )
+        .into_response()
+}
+
+pub async fn robots_txt(State(state_mutex): State<Arc<Mutex<BlogState>>>) -> Response {
+    let mut state = state_mutex.lock().unwrap();
+    state.refresh();
+    (
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        state.robots_txt.clone(),
+    )
+        .into_response()
+}
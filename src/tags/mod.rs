@@ -0,0 +1,181 @@
+// Content tags for posts and projects, defined in `tags.yml` next to the binary.
+// The file is reloaded along with the posts, so adding a tag doesn't need a rebuild:
+//
+// - id: GodotEngine
+//   name: Godot Engine
+//   description: Games and tools made with Godot.
+//   colour: "#478cbf"
+//   icon: sports_esports
+//   aliases: [godot]
+//
+// `id` is what frontmatter and `?filters[0]=` queries use, its lowercase form is the tag's URL.
+// Frontmatter may also use any of the `aliases`. Both are matched case-insensitively.
+
+pub mod page;
+
+use std::{collections::HashMap, fmt::Display, hash::Hash, sync::Arc, time::SystemTime};
+
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::blog::diagnostics::{yaml_error_message, IndexDiagnostic, IndexDiagnosticKind};
+
+pub const TAGS_CONFIG_PATH: &str = "./tags.yml";
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct TagInfo {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    // Any CSS colour, used for the tag's chips.
+    #[serde(default, alias = "color")]
+    pub colour: Option<String>,
+    // A Material Symbols icon name, shown in the tag's chips.
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+// A tag from `tags.yml`. Cheap to clone, and compared and serialized by its id.
+#[derive(Clone, Debug)]
+pub struct ContentTag(Arc<TagInfo>);
+
+impl ContentTag {
+    pub fn id(&self) -> &str {
+        &self.0.id
+    }
+
+    pub fn info(&self) -> &TagInfo {
+        &self.0
+    }
+
+    // The tag's name in URLs, like `/tags/godotengine`.
+    pub fn slug(&self) -> String {
+        self.0.id.to_lowercase()
+    }
+}
+
+impl PartialEq for ContentTag {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.id == other.0.id
+    }
+}
+
+impl Eq for ContentTag {}
+
+impl Hash for ContentTag {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.id.hash(state);
+    }
+}
+
+impl Display for ContentTag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0.name)
+    }
+}
+
+impl Serialize for ContentTag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0.id)
+    }
+}
+
+// Every tag in `tags.yml`, in file order.
+#[derive(Default, Debug)]
+pub struct TagRegistry {
+    tags: Vec<ContentTag>,
+    // Lowercase ids and aliases.
+    lookup: HashMap<String, ContentTag>,
+}
+
+impl TagRegistry {
+    // Never fails: a missing or broken `tags.yml` results in no tags and a diagnostic, and
+    // posts are then indexed without their tags.
+    pub fn load(diagnostics: &mut Vec<IndexDiagnostic>) -> Self {
+        match std::fs::read_to_string(TAGS_CONFIG_PATH) {
+            Ok(yaml) => TagRegistry::parse(&yaml, diagnostics),
+            Err(err) => {
+                diagnostics.push(IndexDiagnostic::new(
+                    TAGS_CONFIG_PATH,
+                    None,
+                    IndexDiagnosticKind::Io(err.to_string()),
+                ));
+                TagRegistry::default()
+            }
+        }
+    }
+
+    // The contents of `tags.yml`.
+    pub fn parse(yaml: &str, diagnostics: &mut Vec<IndexDiagnostic>) -> Self {
+        let mut report = |line: Option<usize>, kind: IndexDiagnosticKind| {
+            diagnostics.push(IndexDiagnostic::new(TAGS_CONFIG_PATH, line, kind));
+        };
+        let tag_infos = match serde_yml::from_str::<Vec<TagInfo>>(yaml) {
+            Ok(tag_infos) => tag_infos,
+            Err(err) => {
+                let line = err.location().map(|location| location.line());
+                report(line, IndexDiagnosticKind::Yaml(yaml_error_message(&err)));
+                return TagRegistry::default();
+            }
+        };
+        let mut registry = TagRegistry::default();
+        // Where each `- id:` entry starts, to point duplicates at the right line.
+        let entry_lines: Vec<usize> = yaml
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| line.starts_with('-'))
+            .map(|(line, _)| line + 1)
+            .collect();
+        for (entry, tag_info) in tag_infos.into_iter().enumerate() {
+            let tag = ContentTag(Arc::new(tag_info));
+            // A tag whose id is taken is dropped, a taken alias only loses that alias.
+            for (i, name) in std::iter::once(&tag.0.id).chain(&tag.0.aliases).enumerate() {
+                let key = name.to_lowercase();
+                if registry.lookup.contains_key(&key) {
+                    report(
+                        entry_lines.get(entry).copied(),
+                        IndexDiagnosticKind::DuplicateTag(name.clone()),
+                    );
+                    match i {
+                        0 => break,
+                        _ => continue,
+                    }
+                }
+                registry.lookup.insert(key, tag.clone());
+                if i == 0 {
+                    registry.tags.push(tag.clone());
+                }
+            }
+        }
+        registry
+    }
+
+    pub fn all(&self) -> &[ContentTag] {
+        &self.tags
+    }
+
+    // Finds a tag by id or alias, as written in frontmatter.
+    pub fn resolve(&self, name: &str) -> Option<&ContentTag> {
+        self.lookup.get(&name.trim().to_lowercase())
+    }
+
+    // Finds a tag by the `slug` in its URL. Aliases work too.
+    pub fn by_slug(&self, slug: &str) -> Option<&ContentTag> {
+        self.resolve(slug)
+    }
+
+    // Display name for a tag id, falling back to the id itself.
+    pub fn name_of(&self, id: &str) -> String {
+        self.resolve(id)
+            .map_or(id.to_string(), |tag| tag.0.name.clone())
+    }
+}
+
+pub fn get_tags_modified_timestamp() -> Option<SystemTime> {
+    std::fs::metadata(TAGS_CONFIG_PATH)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
@@ -0,0 +1,132 @@
+// Tag landing pages, `/tags` and `/tags/{tag}`.
+// Tag chips link here instead of to a filtered `/blog` list, so every tag has a page of its own
+// that search engines can index.
+
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+};
+use maud::html;
+
+use crate::{
+    blog::{page::get_posts_list_markup, BlogPostIndex, PreviewMode},
+    helpers::{PageMeta, PageType, WebPage},
+    projects::{self, ProjectIndex},
+    tags::{ContentTag, TagRegistry},
+    BlogState,
+};
+
+// Tags carried by at least one published post or project, for the sitemap.
+pub fn used_tags<'a>(
+    index: &BlogPostIndex,
+    projects: &ProjectIndex,
+    tags: &'a TagRegistry,
+) -> Vec<&'a ContentTag> {
+    let post_counts = index.tag_counts(PreviewMode(false));
+    tags.all()
+        .iter()
+        .filter(|tag| post_counts.contains_key(tag) || !projects.with_tag(tag).is_empty())
+        .collect()
+}
+
+pub async fn tags_page(
+    preview: PreviewMode,
+    State(state_mutex): State<Arc<Mutex<BlogState>>>,
+) -> WebPage {
+    let mut state = state_mutex.lock().unwrap();
+    state.refresh();
+    let post_counts = state.index.tag_counts(preview);
+    WebPage {
+        title: "Tags".into(),
+        content: html! {
+            h4 .large { "Tags" }
+            div .medium-space {}
+            ul .list .border {
+                @for tag in state.tags.all() {
+                    @let post_count = post_counts.get(tag).copied().unwrap_or(0);
+                    @let project_count = state.projects.with_tag(tag).len();
+                    li {
+                        @if let Some(icon) = &tag.info().icon {
+                            i { (icon) }
+                        }
+                        a .max href={ "/tags/" (tag.slug()) (preview.query_suffix()) } {
+                            h6 .large { (tag) }
+                            @if !tag.info().description.is_empty() {
+                                p { (tag.info().description) }
+                            }
+                            p {
+                                (post_count) @if post_count == 1 { " post" } @else { " posts" }
+                                " · "
+                                (project_count) @if project_count == 1 { " project" } @else { " projects" }
+                            }
+                        }
+                    }
+                }
+            }
+        },
+        page_type: PageType::Blog,
+        status: StatusCode::OK,
+        meta: PageMeta::new(
+            "Every topic Jordi writes about, with its posts and projects.",
+            "/tags",
+        ),
+    }
+}
+
+pub async fn tag_page(
+    Path(slug): Path<String>,
+    preview: PreviewMode,
+    State(state_mutex): State<Arc<Mutex<BlogState>>>,
+) -> WebPage {
+    let mut state = state_mutex.lock().unwrap();
+    state.refresh();
+    let tag = match state.tags.by_slug(&slug) {
+        Some(tag) => tag.clone(),
+        None => return WebPage::not_found("Tag not found."),
+    };
+    let posts: Vec<_> = state
+        .index
+        .as_sorted_vec(preview)
+        .into_iter()
+        .filter(|(_, post_info)| post_info.tags.contains(&tag))
+        .collect();
+    let tag_projects = state.projects.with_tag(&tag);
+    let tag_query = format!("filters[0]={}", tag.id());
+    let description = match tag.info().description.is_empty() {
+        true => format!("Jordi's blog posts and projects about {}.", tag),
+        false => tag.info().description.clone(),
+    };
+    WebPage {
+        title: format!("{} Posts and Projects", tag),
+        content: html! {
+            h4 .large { (tag) }
+            @if !tag.info().description.is_empty() {
+                p .large-text { (tag.info().description) }
+            }
+            p {
+                a href={ "/blog?" (tag_query) } { "Filter the blog list" }
+                " · "
+                a href={ "/feed.xml?" (tag_query) } { "RSS feed" }
+            }
+            div .medium-space {}
+            h5 { "Posts" }
+            div .space {}
+            @if posts.is_empty() {
+                p { "No posts with this tag yet." }
+            } @else {
+                (get_posts_list_markup(posts, preview, None))
+            }
+            @if !tag_projects.is_empty() {
+                div .large-space {}
+                h5 { "Projects" }
+                div .space {}
+                (projects::project_cards(&tag_projects, &state.index, preview))
+            }
+        },
+        page_type: PageType::Blog,
+        status: StatusCode::OK,
+        meta: PageMeta::new(description, format!("/tags/{}", tag.slug())),
+    }
+}